@@ -0,0 +1,307 @@
+//! Chains of joltage adapters, from day 10, with the rules made configurable.
+//!
+//! A chain starts at the outlet (0 jolts) and ends at the device, which is rated a fixed
+//! offset above the highest adapter. Each step up the chain has to be one of the allowed
+//! gaps. The puzzle's rules are gaps of 1, 2 or 3 and a device offset of 3, but nothing here
+//! depends on that.
+//!
+//! The number of arrangements grows exponentially with the length of the chain, so it's
+//! counted with a `BigUint`; enumerating them is only practical for short chains, but sampling
+//! them uniformly at random works for any length.
+use std::collections::BTreeMap;
+use std::fmt;
+
+use num_bigint::{BigUint, RandBigInt};
+use rand::Rng;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    /// The differences in joltage allowed between one link in the chain and the next.
+    pub gaps: Vec<i64>,
+    /// How far above the highest adapter the device is rated. Has to be positive, or the
+    /// device wouldn't be the end of the chain.
+    pub device_offset: i64,
+}
+
+impl Default for Rules {
+    /// The puzzle's rules.
+    fn default() -> Self {
+        Rules {
+            gaps: vec![1, 2, 3],
+            device_offset: 3,
+        }
+    }
+}
+
+/// A step in a chain that isn't one of the allowed gaps.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BrokenChain {
+    pub from: i64,
+    pub to: i64,
+}
+
+impl fmt::Display for BrokenChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "can't go from {} jolts to {} jolts in one step",
+            self.from, self.to
+        )
+    }
+}
+
+impl std::error::Error for BrokenChain {}
+
+/// Why `AdapterChain::new` couldn't make a chain: everything has to be positive.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum InvalidChain {
+    /// An adapter rated at or below the outlet's 0 jolts.
+    Adapter(i64),
+    Gap(i64),
+    DeviceOffset(i64),
+}
+
+impl fmt::Display for InvalidChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidChain::Adapter(a) => write!(
+                f,
+                "adapters must be rated above the outlet's 0 jolts, not {}",
+                a
+            ),
+            InvalidChain::Gap(gap) => write!(f, "gaps must be positive, not {}", gap),
+            InvalidChain::DeviceOffset(offset) => {
+                write!(f, "the device offset must be positive, not {}", offset)
+            }
+        }
+    }
+}
+
+impl std::error::Error for InvalidChain {}
+
+#[derive(Debug, Clone)]
+pub struct AdapterChain {
+    rules: Rules,
+    /// The outlet, every adapter in order, and the device.
+    joltages: Vec<i64>,
+    /// For each entry in `joltages`, how many ways there are to get from it to the device.
+    ways_to_device: Vec<BigUint>,
+}
+
+impl AdapterChain {
+    /// Fails if any of the gaps or adapters, or the device offset, isn't positive.
+    pub fn new(adapters: &[i64], rules: Rules) -> Result<Self, InvalidChain> {
+        if let Some(&a) = adapters.iter().find(|&&a| a <= 0) {
+            return Err(InvalidChain::Adapter(a));
+        }
+        if let Some(&gap) = rules.gaps.iter().find(|&&gap| gap <= 0) {
+            return Err(InvalidChain::Gap(gap));
+        }
+        // The counting below walks from the device back down, so it has to be the highest
+        // joltage in the chain
+        if rules.device_offset <= 0 {
+            return Err(InvalidChain::DeviceOffset(rules.device_offset));
+        }
+        let mut joltages = vec![0];
+        joltages.extend_from_slice(adapters);
+        joltages.sort_unstable();
+        joltages.push(joltages[joltages.len() - 1] + rules.device_offset);
+
+        // Dynamic programming, from the device back down to the outlet. Gaps are positive, so
+        // every link's successors come after it.
+        let mut ways_to_device = vec![BigUint::from(0u32); joltages.len()];
+        ways_to_device[joltages.len() - 1] = BigUint::from(1u32);
+        for i in (0..joltages.len() - 1).rev() {
+            let mut ways = BigUint::from(0u32);
+            for j in successors(&rules, &joltages, i) {
+                ways += &ways_to_device[j];
+            }
+            ways_to_device[i] = ways;
+        }
+        Ok(AdapterChain {
+            rules,
+            joltages,
+            ways_to_device,
+        })
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// The device's joltage rating.
+    pub fn device(&self) -> i64 {
+        self.joltages[self.joltages.len() - 1]
+    }
+
+    /// How many times each gap comes up in the chain that uses every adapter, or where that
+    /// chain breaks the rules.
+    pub fn histogram(&self) -> Result<BTreeMap<i64, usize>, BrokenChain> {
+        let mut histogram = BTreeMap::new();
+        for pair in self.joltages.windows(2) {
+            let (from, to) = (pair[0], pair[1]);
+            if !self.rules.gaps.contains(&(to - from)) {
+                return Err(BrokenChain { from, to });
+            }
+            *histogram.entry(to - from).or_insert(0) += 1;
+        }
+        Ok(histogram)
+    }
+
+    /// How many different sets of adapters can connect the outlet to the device.
+    pub fn arrangements(&self) -> BigUint {
+        self.ways_to_device[0].clone()
+    }
+
+    /// Every arrangement, as the joltages of the adapters it uses. There are exponentially
+    /// many, so this is lazy.
+    pub fn enumerate(&self) -> impl Iterator<Item = Vec<i64>> + '_ {
+        let device = self.joltages.len() - 1;
+        // Depth first, with each path on the stack being indices into `joltages`
+        let mut stack = vec![vec![0]];
+        std::iter::from_fn(move || {
+            while let Some(path) = stack.pop() {
+                let last = path[path.len() - 1];
+                if last == device {
+                    return Some(self.adapters_on(&path));
+                }
+                // Pushed in reverse, so arrangements come out in lexicographic order
+                let next: Vec<_> = successors(&self.rules, &self.joltages, last)
+                    .filter(|&j| self.ways_to_device[j] > BigUint::from(0u32))
+                    .collect();
+                for &j in next.iter().rev() {
+                    let mut path = path.clone();
+                    path.push(j);
+                    stack.push(path);
+                }
+            }
+            None
+        })
+    }
+
+    /// One arrangement, chosen uniformly at random from all of them, or `None` if there
+    /// aren't any.
+    ///
+    /// At each link, the next one is picked with probability proportional to how many ways
+    /// there are to finish the chain from it, which makes every complete chain equally likely.
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<Vec<i64>> {
+        if self.ways_to_device[0] == BigUint::from(0u32) {
+            return None;
+        }
+        let device = self.joltages.len() - 1;
+        let mut path = vec![0];
+        let mut at = 0;
+        while at != device {
+            let mut pick = rng.gen_biguint_below(&self.ways_to_device[at]);
+            for j in successors(&self.rules, &self.joltages, at) {
+                if pick < self.ways_to_device[j] {
+                    at = j;
+                    break;
+                }
+                pick -= &self.ways_to_device[j];
+            }
+            path.push(at);
+        }
+        Some(self.adapters_on(&path))
+    }
+
+    /// The adapters' joltages, from a path of indices that goes from the outlet to the device.
+    fn adapters_on(&self, path: &[usize]) -> Vec<i64> {
+        path[1..path.len() - 1]
+            .iter()
+            .map(|&i| self.joltages[i])
+            .collect()
+    }
+}
+
+/// The indices of the links that can come straight after `joltages[i]`.
+fn successors<'a>(
+    rules: &'a Rules,
+    joltages: &'a [i64],
+    i: usize,
+) -> impl Iterator<Item = usize> + 'a {
+    let max_gap = rules.gaps.iter().copied().max().unwrap_or(0);
+    (i + 1..joltages.len())
+        .take_while(move |&j| joltages[j] - joltages[i] <= max_gap)
+        .filter(move |&j| rules.gaps.contains(&(joltages[j] - joltages[i])))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const SMALL_EXAMPLE: &[i64] = &[16, 10, 15, 5, 1, 11, 7, 19, 6, 12, 4];
+
+    #[test]
+    fn test_puzzle_rules() {
+        let chain = AdapterChain::new(SMALL_EXAMPLE, Rules::default()).unwrap();
+        assert_eq!(chain.device(), 22);
+        let histogram: Vec<_> = chain.histogram().unwrap().into_iter().collect();
+        assert_eq!(histogram, vec![(1, 7), (3, 5)]);
+        assert_eq!(chain.arrangements(), BigUint::from(8u32));
+
+        let all: Vec<_> = chain.enumerate().collect();
+        assert_eq!(all.len(), 8);
+        assert_eq!(all[0], vec![1, 4, 5, 6, 7, 10, 11, 12, 15, 16, 19]);
+        assert_eq!(all[7], vec![1, 4, 7, 10, 12, 15, 16, 19]);
+
+        let mut rng = StdRng::seed_from_u64(2020);
+        for _ in 0..20 {
+            assert!(all.contains(&chain.sample(&mut rng).unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_custom_rules() {
+        let rules = Rules {
+            gaps: vec![1, 2],
+            device_offset: 2,
+        };
+        let chain = AdapterChain::new(&[1, 2, 4], rules).unwrap();
+        assert_eq!(chain.device(), 6);
+        assert_eq!(chain.arrangements(), BigUint::from(2u32));
+        assert_eq!(
+            chain.enumerate().collect::<Vec<_>>(),
+            vec![vec![1, 2, 4], vec![2, 4]]
+        );
+        let broken = AdapterChain::new(&[1, 5], Rules::default()).unwrap();
+        assert_eq!(broken.histogram(), Err(BrokenChain { from: 1, to: 5 }));
+        let unreachable = AdapterChain::new(&[5], Rules::default()).unwrap();
+        assert_eq!(unreachable.arrangements(), BigUint::from(0u32));
+        assert_eq!(unreachable.enumerate().next(), None);
+        assert_eq!(unreachable.sample(&mut StdRng::seed_from_u64(1)), None);
+    }
+
+    #[test]
+    fn test_long_chain() {
+        // Far more arrangements than fit in an i64
+        let adapters: Vec<i64> = (1..=200).collect();
+        let chain = AdapterChain::new(&adapters, Rules::default()).unwrap();
+        assert!(chain.arrangements() > BigUint::from(u64::MAX));
+        let mut rng = StdRng::seed_from_u64(10);
+        let sample = chain.sample(&mut rng).unwrap();
+        assert_eq!(sample.last(), Some(&200));
+        assert!(sample.windows(2).all(|w| (1..=3).contains(&(w[1] - w[0]))));
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        let rules = |gaps: Vec<i64>, device_offset| Rules {
+            gaps,
+            device_offset,
+        };
+        let err = AdapterChain::new(SMALL_EXAMPLE, rules(vec![1, 2, 3], 0)).unwrap_err();
+        assert_eq!(err, InvalidChain::DeviceOffset(0));
+        assert_eq!(err.to_string(), "the device offset must be positive, not 0");
+        assert_eq!(
+            AdapterChain::new(SMALL_EXAMPLE, rules(vec![1, -1], 3)).unwrap_err(),
+            InvalidChain::Gap(-1)
+        );
+        assert_eq!(
+            AdapterChain::new(&[4, 0], Rules::default()).unwrap_err(),
+            InvalidChain::Adapter(0)
+        );
+    }
+}
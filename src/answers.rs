@@ -0,0 +1,205 @@
+//! A record of known-good answers, so a refactored solver can be checked against them.
+//!
+//! Everyone's puzzle input is different, so answers are keyed by a fingerprint of the input as
+//! well as the day and part. The store is a plain tab-separated file, one answer per line:
+//!
+//! ```text
+//! # fingerprint       day  part  answer
+//! 9e3a4c2b7d1f0e58    1    1     514579
+//! ```
+//!
+//! Blank lines and lines starting with `#` are ignored, and aren't kept when the file is saved.
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::hash::Hasher;
+use std::io;
+use std::path::Path;
+
+use fnv::FnvHasher;
+
+/// Where the checked-in answers live, relative to the crate root.
+pub const DEFAULT_ANSWERS_FILE: &str = "answers.tsv";
+
+// Written at the top of the file every time it's saved, since saving doesn't keep comments.
+const HEADER: &str = "\
+# Known-good answers, checked by `aoc2020 verify`. Answers are keyed by a fingerprint of the
+# input, so several people's inputs can live side by side. Add yours with `aoc2020 verify --record`.
+# fingerprint\tday\tpart\tanswer";
+
+/// A fingerprint of a puzzle input: a 64-bit FNV-1a hash, in hex.
+/// Trailing newlines are ignored, since they're trimmed before any solver sees the input.
+pub fn fingerprint(input: &str) -> String {
+    let mut hasher = FnvHasher::default();
+    hasher.write(input.trim_end_matches('\n').as_bytes());
+    format!("{:016x}", hasher.finish())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Key {
+    fingerprint: String,
+    day: u32,
+    part: u32,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AnswerStore {
+    // A BTreeMap so the file comes out in the same order every time it's saved.
+    answers: BTreeMap<Key, String>,
+}
+
+impl AnswerStore {
+    pub fn new() -> Self {
+        AnswerStore::default()
+    }
+
+    /// Reads the store from `path`. A file that doesn't exist yet is just an empty store.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(contents) => AnswerStore::parse(&contents).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: {}", path.display(), e),
+                )
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(AnswerStore::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let mut store = AnswerStore::new();
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<_> = line.split('\t').map(str::trim).collect();
+            let parsed = match fields.as_slice() {
+                [fingerprint, day, part, answer] => day
+                    .parse()
+                    .ok()
+                    .zip(part.parse().ok())
+                    .map(|(day, part)| (fingerprint, day, part, answer)),
+                _ => None,
+            };
+            let (fingerprint, day, part, answer) = parsed.ok_or_else(|| {
+                format!(
+                    "line {}: expected fingerprint, day, part and answer separated by tabs, found {:?}",
+                    i + 1,
+                    line
+                )
+            })?;
+            store.insert(fingerprint, day, part, answer);
+        }
+        Ok(store)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    pub fn get(&self, fingerprint: &str, day: u32, part: u32) -> Option<&str> {
+        let key = Key {
+            fingerprint: fingerprint.to_owned(),
+            day,
+            part,
+        };
+        self.answers.get(&key).map(String::as_str)
+    }
+
+    pub fn insert(&mut self, fingerprint: &str, day: u32, part: u32, answer: &str) {
+        let key = Key {
+            fingerprint: fingerprint.to_owned(),
+            day,
+            part,
+        };
+        self.answers.insert(key, answer.to_owned());
+    }
+
+    pub fn len(&self) -> usize {
+        self.answers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.answers.is_empty()
+    }
+
+    /// Checks an answer against the store.
+    pub fn check(&self, fingerprint: &str, day: u32, part: u32, answer: &str) -> Verdict {
+        match self.get(fingerprint, day, part) {
+            None => Verdict::Missing,
+            Some(expected) if expected == answer => Verdict::Pass,
+            Some(expected) => Verdict::Fail {
+                expected: expected.to_owned(),
+            },
+        }
+    }
+}
+
+impl fmt::Display for AnswerStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", HEADER)?;
+        for (key, answer) in &self.answers {
+            writeln!(
+                f,
+                "{}\t{}\t{}\t{}",
+                key.fingerprint, key.day, key.part, answer
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail {
+        expected: String,
+    },
+    /// There's no recorded answer for this input, day and part.
+    Missing,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint() {
+        assert_eq!(fingerprint("1\n2\n3\n"), fingerprint("1\n2\n3"));
+        assert_ne!(fingerprint("1\n2\n3"), fingerprint("1\n2\n4"));
+        assert_eq!(fingerprint("").len(), 16);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut store = AnswerStore::new();
+        store.insert("00000000000000ff", 19, 2, "12");
+        store.insert("00000000000000ff", 1, 1, "514579");
+        store.insert("0123456789abcdef", 21, 2, "mxmxvkd,sqjhc,fvjkl");
+        let parsed = AnswerStore::parse(&store.to_string()).unwrap();
+        assert_eq!(parsed, store);
+        assert_eq!(parsed.get("00000000000000ff", 1, 1), Some("514579"));
+        assert_eq!(
+            parsed.check("00000000000000ff", 1, 1, "514579"),
+            Verdict::Pass
+        );
+        assert_eq!(
+            parsed.check("00000000000000ff", 19, 2, "11"),
+            Verdict::Fail {
+                expected: "12".to_owned()
+            }
+        );
+        assert_eq!(
+            parsed.check("00000000000000ff", 19, 1, "2"),
+            Verdict::Missing
+        );
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let err = AnswerStore::parse("# comment\n\nabc\t1\tx\t5\n").unwrap_err();
+        assert!(err.starts_with("line 3:"), "{}", err);
+    }
+}
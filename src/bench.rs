@@ -0,0 +1,276 @@
+//! Timing every day's parser and solvers, and comparing the timings against an earlier run.
+//!
+//! Each stage (parsing, part 1, part 2) is run several times and we keep the min, median and
+//! mean. Comparisons against a baseline use the median, which is the least noisy of the three.
+//! Reports can be saved as JSON or CSV and read back in either format to use as a baseline.
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ParseError;
+use crate::solution::Day;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stage {
+    Parse,
+    Part1,
+    Part2,
+}
+
+impl Stage {
+    fn of_part(part: u32) -> Stage {
+        if part == 1 {
+            Stage::Part1
+        } else {
+            Stage::Part2
+        }
+    }
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Stage::Parse => "parse",
+            Stage::Part1 => "part1",
+            Stage::Part2 => "part2",
+        };
+        f.pad(name)
+    }
+}
+
+/// Timings for one stage of one day, in nanoseconds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Timing {
+    pub day: u32,
+    pub stage: Stage,
+    pub runs: usize,
+    pub min_ns: u64,
+    pub median_ns: u64,
+    pub mean_ns: u64,
+}
+
+impl Timing {
+    fn new(day: u32, stage: Stage, mut samples: Vec<Duration>) -> Timing {
+        samples.sort();
+        let nanos = |d: Duration| d.as_nanos() as u64;
+        let total: Duration = samples.iter().sum();
+        Timing {
+            day,
+            stage,
+            runs: samples.len(),
+            min_ns: nanos(samples[0]),
+            median_ns: nanos(samples[samples.len() / 2]),
+            mean_ns: nanos(total / samples.len() as u32),
+        }
+    }
+}
+
+/// Runs `f` `runs` times (at least once), returning how long each run took and the last result.
+fn time<T>(runs: usize, mut f: impl FnMut() -> T) -> (Vec<Duration>, T) {
+    let mut samples = Vec::with_capacity(runs);
+    loop {
+        let start = Instant::now();
+        let result = f();
+        samples.push(start.elapsed());
+        if samples.len() >= runs {
+            return (samples, result);
+        }
+    }
+}
+
+/// Times the parser and each part of one day on `input`.
+pub fn bench_day(day: &Day, input: &str, runs: usize) -> Result<Vec<Timing>, ParseError> {
+    let input = input.trim_end_matches('\n');
+    let (samples, parsed) = time(runs, || day.parse(input));
+    let parsed = parsed?;
+    let mut timings = vec![Timing::new(day.day, Stage::Parse, samples)];
+    for &part in day.parts {
+        let (samples, _) = time(runs, || day.solve(&parsed, part));
+        timings.push(Timing::new(day.day, Stage::of_part(part), samples));
+    }
+    Ok(timings)
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Report {
+    pub timings: Vec<Timing>,
+}
+
+const CSV_HEADER: &str = "day,stage,runs,min_ns,median_ns,mean_ns";
+
+impl Report {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> Result<Report, String> {
+        serde_json::from_str(json).map_err(|e| e.to_string())
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = format!("{}\n", CSV_HEADER);
+        for t in &self.timings {
+            csv += &format!(
+                "{},{},{},{},{},{}\n",
+                t.day, t.stage, t.runs, t.min_ns, t.median_ns, t.mean_ns
+            );
+        }
+        csv
+    }
+
+    pub fn from_csv(csv: &str) -> Result<Report, String> {
+        let mut lines = csv.lines().enumerate();
+        match lines.next() {
+            Some((_, header)) if header.trim() == CSV_HEADER => {}
+            _ => return Err(format!("expected the header {:?}", CSV_HEADER)),
+        }
+        let mut timings = vec![];
+        for (i, line) in lines.filter(|(_, line)| !line.trim().is_empty()) {
+            let bad = || format!("line {}: expected {}, found {:?}", i + 1, CSV_HEADER, line);
+            let fields: Vec<_> = line.trim().split(',').collect();
+            if fields.len() != 6 {
+                return Err(bad());
+            }
+            let stage = match fields[1] {
+                "parse" => Stage::Parse,
+                "part1" => Stage::Part1,
+                "part2" => Stage::Part2,
+                _ => return Err(bad()),
+            };
+            let number = |i: usize| fields[i].parse::<u64>().map_err(|_| bad());
+            timings.push(Timing {
+                day: number(0)? as u32,
+                stage,
+                runs: number(2)? as usize,
+                min_ns: number(3)?,
+                median_ns: number(4)?,
+                mean_ns: number(5)?,
+            });
+        }
+        Ok(Report { timings })
+    }
+
+    /// Saves the report as CSV if the path ends in ".csv", and JSON otherwise.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if is_csv(path) {
+            fs::write(path, self.to_csv())
+        } else {
+            fs::write(path, self.to_json())
+        }
+    }
+
+    /// Reads a report saved by `save`.
+    pub fn load(path: &Path) -> io::Result<Report> {
+        let contents = fs::read_to_string(path)?;
+        let report = if is_csv(path) {
+            Report::from_csv(&contents)
+        } else {
+            Report::from_json(&contents)
+        };
+        report.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn get(&self, day: u32, stage: Stage) -> Option<&Timing> {
+        self.timings
+            .iter()
+            .find(|t| t.day == day && t.stage == stage)
+    }
+
+    /// Compares every timing in this report against the same day and stage in `baseline`.
+    /// A stage counts as a regression if its median is more than `threshold` (e.g. 0.2 for 20%)
+    /// slower than the baseline's.
+    pub fn compare(&self, baseline: &Report, threshold: f64) -> Vec<Comparison> {
+        self.timings
+            .iter()
+            .filter_map(|t| {
+                let before = baseline.get(t.day, t.stage)?;
+                let change = t.median_ns as f64 / (before.median_ns.max(1) as f64) - 1.0;
+                Some(Comparison {
+                    day: t.day,
+                    stage: t.stage,
+                    baseline_ns: before.median_ns,
+                    current_ns: t.median_ns,
+                    change,
+                    regression: change > threshold,
+                })
+            })
+            .collect()
+    }
+}
+
+fn is_csv(path: &Path) -> bool {
+    path.extension() == Some("csv".as_ref())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub day: u32,
+    pub stage: Stage,
+    pub baseline_ns: u64,
+    pub current_ns: u64,
+    /// How much slower the current median is, as a fraction: 0.5 is 50% slower, -0.5 twice as fast.
+    pub change: f64,
+    pub regression: bool,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn timing(day: u32, stage: Stage, median_ns: u64) -> Timing {
+        Timing {
+            day,
+            stage,
+            runs: 3,
+            min_ns: median_ns - 1,
+            median_ns,
+            mean_ns: median_ns + 1,
+        }
+    }
+
+    #[test]
+    fn test_bench_day() {
+        let day1 = crate::solution::day(1).unwrap();
+        let timings = bench_day(day1, "1721\n979\n366\n299\n675\n1456\n", 3).unwrap();
+        let stages: Vec<_> = timings.iter().map(|t| (t.stage, t.runs)).collect();
+        assert_eq!(
+            stages,
+            vec![(Stage::Parse, 3), (Stage::Part1, 3), (Stage::Part2, 3)]
+        );
+        assert!(timings.iter().all(|t| t.min_ns <= t.median_ns));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let report = Report {
+            timings: vec![timing(1, Stage::Parse, 100), timing(25, Stage::Part1, 2000)],
+        };
+        assert_eq!(Report::from_json(&report.to_json()).unwrap(), report);
+        assert_eq!(Report::from_csv(&report.to_csv()).unwrap(), report);
+        assert!(Report::from_csv("day,stage\n1,parse").is_err());
+    }
+
+    #[test]
+    fn test_compare() {
+        let baseline = Report {
+            timings: vec![timing(1, Stage::Part1, 100), timing(1, Stage::Part2, 100)],
+        };
+        let current = Report {
+            timings: vec![
+                timing(1, Stage::Part1, 110),
+                timing(1, Stage::Part2, 150),
+                timing(2, Stage::Part1, 100),
+            ],
+        };
+        let comparisons = current.compare(&baseline, 0.2);
+        assert_eq!(comparisons.len(), 2);
+        assert!(!comparisons[0].regression);
+        assert!(comparisons[1].regression);
+        assert!((comparisons[1].change - 0.5).abs() < 1e-9);
+    }
+}
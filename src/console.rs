@@ -0,0 +1,1174 @@
+//! The handheld game console from day 8, as an interpreter that can be used on its own.
+//!
+//! A program is one instruction per line: a three-letter opcode and a signed argument, like
+//! `acc +1` or `jmp -4`. The console has a program counter and a single accumulator. Besides
+//! the built-in `acc`, `jmp` and `nop`, an `InstructionSet` can have extra opcodes registered,
+//! each with its own semantics, for puzzles that extend the console.
+//!
+//! Unlike the `Execution` day 8 started out with, a `Machine` never panics on a bad jump: the
+//! program counter leaving the program in either direction is just another way for it to halt.
+//! Nor does it panic (or silently wrap, in release builds) when the accumulator overflows: the
+//! accumulator can be an `i32`, `i64` or `i128`, and an `Overflow` policy says what to do.
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::hash::Hash;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::error::{ParseError, Source};
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Instruction {
+    /// Adds the argument to the accumulator.
+    Acc(i32),
+    /// Jumps by the argument, relative to this instruction.
+    Jmp(i32),
+    /// Does nothing.
+    Nop(i32),
+    /// An opcode registered with `InstructionSet::register`.
+    Custom(&'static str, i32),
+}
+
+impl fmt::Display for Instruction {
+    /// The same format `InstructionSet::parse` reads, e.g. `jmp -4`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {:+}", self.opcode(), self.arg())
+    }
+}
+
+impl Instruction {
+    pub fn opcode(&self) -> &'static str {
+        match self {
+            Instruction::Acc(_) => "acc",
+            Instruction::Jmp(_) => "jmp",
+            Instruction::Nop(_) => "nop",
+            Instruction::Custom(opcode, _) => opcode,
+        }
+    }
+
+    pub fn arg(&self) -> i32 {
+        match *self {
+            Instruction::Acc(arg)
+            | Instruction::Jmp(arg)
+            | Instruction::Nop(arg)
+            | Instruction::Custom(_, arg) => arg,
+        }
+    }
+}
+
+fn is_label(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// The types the accumulator can be.
+pub trait Accumulator:
+    Copy + Default + Eq + Hash + fmt::Debug + fmt::Display + FromStr + From<i32> + Serialize
+{
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn wrapping_add(self, rhs: Self) -> Self;
+    fn saturating_add(self, rhs: Self) -> Self;
+}
+
+macro_rules! accumulator {
+    ($($t:ty),*) => {$(
+        impl Accumulator for $t {
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_add(self, rhs)
+            }
+
+            fn wrapping_add(self, rhs: Self) -> Self {
+                <$t>::wrapping_add(self, rhs)
+            }
+
+            fn saturating_add(self, rhs: Self) -> Self {
+                <$t>::saturating_add(self, rhs)
+            }
+        }
+    )*};
+}
+
+accumulator!(i32, i64, i128);
+
+/// What happens when adding to the accumulator overflows.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Overflow {
+    /// The machine halts with `Halt::Overflow`, without running the instruction.
+    #[default]
+    Error,
+    Wrap,
+    Saturate,
+}
+
+/// An addition to the accumulator overflowed, under `Overflow::Error`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct AccOverflow;
+
+impl Overflow {
+    pub fn add<A: Accumulator>(self, acc: A, rhs: A) -> Result<A, AccOverflow> {
+        match self {
+            Overflow::Error => acc.checked_add(rhs).ok_or(AccOverflow),
+            Overflow::Wrap => Ok(acc.wrapping_add(rhs)),
+            Overflow::Saturate => Ok(acc.saturating_add(rhs)),
+        }
+    }
+}
+
+/// The machine's state, as seen by an instruction's semantics.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Registers<A = i32> {
+    /// Wide enough that no sequence of `i32` jumps in a program that fits in memory can
+    /// overflow it.
+    pub pc: i64,
+    pub acc: A,
+}
+
+/// What a registered opcode does, given the registers, the instruction's argument and the
+/// machine's overflow policy. It's responsible for moving the program counter too, which is
+/// usually just `pc += 1`, and for giving `AccOverflow` if the policy says an overflow is an
+/// error; `Overflow::add` does that for additions.
+pub type Semantics<A> = fn(&mut Registers<A>, i32, Overflow) -> Result<(), AccOverflow>;
+
+const BUILT_IN: [&str; 3] = ["acc", "jmp", "nop"];
+
+/// The opcodes a program can use: the built-in ones, plus any that have been registered.
+/// The semantics of registered opcodes depend on the type of the accumulator, so the
+/// instruction set does too.
+#[derive(Clone)]
+pub struct InstructionSet<A = i32> {
+    custom: HashMap<&'static str, Semantics<A>>,
+}
+
+impl<A> Default for InstructionSet<A> {
+    fn default() -> Self {
+        InstructionSet {
+            custom: HashMap::new(),
+        }
+    }
+}
+
+impl<A> fmt::Debug for InstructionSet<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut opcodes: Vec<_> = BUILT_IN.iter().chain(self.custom.keys()).collect();
+        opcodes.sort();
+        f.debug_set().entries(opcodes).finish()
+    }
+}
+
+impl InstructionSet {
+    /// Just `acc`, `jmp` and `nop`, for an `i32` accumulator. For wider accumulators, use
+    /// `InstructionSet::default()`.
+    pub fn new() -> Self {
+        InstructionSet::default()
+    }
+}
+
+impl<A: Accumulator> InstructionSet<A> {
+    /// Adds an opcode, or replaces the semantics of one that was registered before.
+    ///
+    /// Panics if `opcode` is one of the built-in ones, or isn't a single word.
+    pub fn register(&mut self, opcode: &'static str, semantics: Semantics<A>) -> &mut Self {
+        assert!(
+            !BUILT_IN.contains(&opcode),
+            "{} is built in and can't be redefined",
+            opcode
+        );
+        assert!(
+            !opcode.is_empty() && !opcode.contains(char::is_whitespace),
+            "opcodes must be a single word, not {:?}",
+            opcode
+        );
+        self.custom.insert(opcode, semantics);
+        self
+    }
+
+    pub fn contains(&self, opcode: &str) -> bool {
+        BUILT_IN.contains(&opcode) || self.custom.contains_key(opcode)
+    }
+
+    /// Parses a program, one instruction per line. Errors point at the offending opcode or
+    /// argument.
+    pub fn parse(&self, input: &str) -> Result<Vec<Instruction>, ProgramError> {
+        let src = program_source(input);
+        input
+            .lines()
+            .map(|line| self.parse_line(&src, line))
+            .collect::<Result<_, _>>()
+            .map_err(ProgramError::from)
+    }
+
+    fn parse_line(&self, src: &Source, line: &str) -> Result<Instruction, ParseError> {
+        let (opcode, arg) = match line.find(' ') {
+            Some(i) => (&line[..i], &line[i + 1..]),
+            None => {
+                return Err(src.error(
+                    line,
+                    format!("expected an instruction like \"acc +1\", found {:?}", line),
+                ))
+            }
+        };
+        let arg = arg.parse().map_err(|_| {
+            src.error(
+                arg,
+                format!("expected an argument like +1 or -4, found {:?}", arg),
+            )
+        })?;
+        self.instruction(src, opcode, arg)
+    }
+
+    fn instruction(&self, src: &Source, opcode: &str, arg: i32) -> Result<Instruction, ParseError> {
+        Ok(match opcode {
+            "acc" => Instruction::Acc(arg),
+            "jmp" => Instruction::Jmp(arg),
+            "nop" => Instruction::Nop(arg),
+            _ => match self.custom.get_key_value(opcode) {
+                Some((&opcode, _)) => Instruction::Custom(opcode, arg),
+                None => {
+                    return Err(src.error(opcode, format!("unknown opcode {:?}", opcode)));
+                }
+            },
+        })
+    }
+
+    /// Assembles a hand-written program. On top of what `parse` accepts, it allows:
+    /// - comments, from a `#` to the end of the line
+    /// - blank lines, and any amount of whitespace around opcodes and arguments
+    /// - labels, like `loop:`, either on their own line or before an instruction; a label
+    ///   can be used in place of an argument, and becomes the offset from that instruction
+    ///   to the label. A label at the very end of the program points just past the last
+    ///   instruction, where the program terminates.
+    pub fn assemble(&self, input: &str) -> Result<Vec<Instruction>, ProgramError> {
+        self.assemble_source(&program_source(input))
+            .map_err(ProgramError::from)
+    }
+
+    fn assemble_source(&self, src: &Source) -> Result<Vec<Instruction>, ParseError> {
+        let input = src.input;
+        // First pass: where each label is, and the opcode and argument of each instruction
+        let mut labels = HashMap::new();
+        let mut lines = vec![];
+        for line in input.lines() {
+            let mut code = line.split('#').next().unwrap();
+            if let Some(i) = code.find(':') {
+                let label = code[..i].trim();
+                if !is_label(label) {
+                    return Err(src.error(
+                        code,
+                        format!("expected a label like \"loop:\", found {:?}", &code[..=i]),
+                    ));
+                }
+                if labels.insert(label, lines.len() as i64).is_some() {
+                    return Err(src.error(label, format!("label {:?} is defined twice", label)));
+                }
+                code = &code[i + 1..];
+            }
+            let words: Vec<_> = code.split_whitespace().collect();
+            match words.as_slice() {
+                [] => {}
+                &[opcode, arg] => lines.push((opcode, arg)),
+                _ => {
+                    return Err(src.error(
+                        code.trim_start(),
+                        format!(
+                            "expected an instruction like \"acc +1\", found {:?}",
+                            code.trim()
+                        ),
+                    ))
+                }
+            }
+        }
+        // Second pass: resolve the labels
+        let mut program = vec![];
+        for (pc, (opcode, arg)) in lines.into_iter().enumerate() {
+            let arg = match (arg.parse(), labels.get(arg)) {
+                (Ok(arg), _) => arg,
+                (Err(_), Some(&target)) => (target - pc as i64) as i32,
+                (Err(_), None) if is_label(arg) => {
+                    return Err(src.error(arg, format!("unknown label {:?}", arg)))
+                }
+                (Err(_), None) => {
+                    return Err(src.error(
+                        arg,
+                        format!(
+                            "expected an argument like +1 or -4, or a label, found {:?}",
+                            arg
+                        ),
+                    ))
+                }
+            };
+            program.push(self.instruction(src, opcode, arg)?);
+        }
+        Ok(program)
+    }
+
+    fn execute(
+        &self,
+        instr: Instruction,
+        regs: &mut Registers<A>,
+        overflow: Overflow,
+    ) -> Result<(), Halt> {
+        let pc = regs.pc;
+        let overflowed = |AccOverflow| Halt::Overflow { pc };
+        match instr {
+            Instruction::Acc(arg) => {
+                regs.acc = overflow.add(regs.acc, A::from(arg)).map_err(overflowed)?;
+                regs.pc += 1;
+            }
+            Instruction::Jmp(arg) => regs.pc += arg as i64,
+            Instruction::Nop(_) => regs.pc += 1,
+            Instruction::Custom(opcode, arg) => match self.custom.get(opcode) {
+                Some(semantics) => {
+                    // Semantics that fail halfway shouldn't leave the registers half-updated
+                    let mut updated = *regs;
+                    semantics(&mut updated, arg, overflow).map_err(overflowed)?;
+                    *regs = updated;
+                }
+                None => return Err(Halt::UnknownOpcode { pc }),
+            },
+        }
+        Ok(())
+    }
+}
+
+/// Where in a program's source `InstructionSet::parse` or `assemble` gave up, and why. The
+/// console doesn't know which puzzle a program is from; `into_parse_error` adds the day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramError {
+    /// 1-based. A line of 0 means the error isn't about any one line.
+    pub line: usize,
+    /// 1-based, counted in characters.
+    pub column: usize,
+    pub message: String,
+}
+
+impl ProgramError {
+    pub fn into_parse_error(self, day: u32) -> ParseError {
+        ParseError::new(day, self.line, self.column, self.message)
+    }
+}
+
+impl From<ParseError> for ProgramError {
+    fn from(e: ParseError) -> Self {
+        ProgramError {
+            line: e.line,
+            column: e.column,
+            message: e.message,
+        }
+    }
+}
+
+impl fmt::Display for ProgramError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(
+                f,
+                "line {}, column {}: {}",
+                self.line, self.column, self.message
+            )
+        }
+    }
+}
+
+impl Error for ProgramError {}
+
+/// `Source` is how every parser here works out line and column numbers, but it wants a day.
+/// The console's errors become `ProgramError`s before anyone sees them, which drops it again.
+fn program_source(input: &str) -> Source<'_> {
+    Source::new(0, input)
+}
+
+/// Why a machine stopped.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Halt {
+    /// The program counter landed just past the last instruction, which is how a program
+    /// ends normally.
+    Terminated,
+    /// The program counter jumped to before the first instruction.
+    PcBeforeStart { pc: i64 },
+    /// The program counter jumped further past the end than the instruction after the last.
+    PcPastEnd { pc: i64 },
+    /// The step limit ran out.
+    StepLimit,
+    /// The instruction at `pc` was about to run for a second time. Only `run_until_repeat`
+    /// checks for this.
+    Loop { pc: i64 },
+    /// The instruction at `pc` would have overflowed the accumulator, under
+    /// `Overflow::Error`. It hasn't been run, so the machine is still at it.
+    Overflow { pc: i64 },
+    /// The instruction at `pc` has a registered opcode that isn't in the machine's instruction
+    /// set, e.g. because the program was parsed with a different set. It hasn't been run.
+    UnknownOpcode { pc: i64 },
+}
+
+impl Halt {
+    /// Whether the program ran to its end.
+    pub fn is_terminated(&self) -> bool {
+        *self == Halt::Terminated
+    }
+}
+
+/// A program being run.
+#[derive(Debug, Clone)]
+pub struct Machine<A = i32> {
+    program: Vec<Instruction>,
+    instructions: InstructionSet<A>,
+    regs: Registers<A>,
+    overflow: Overflow,
+    steps: u64,
+    step_limit: Option<u64>,
+    trace: Option<Trace<A>>,
+}
+
+impl Machine {
+    /// A machine with an `i32` accumulator, for a program that only uses the built-in
+    /// opcodes. For wider accumulators, use `with_instructions` and `InstructionSet::default()`.
+    pub fn new(program: Vec<Instruction>) -> Self {
+        Machine::with_instructions(program, InstructionSet::new())
+    }
+}
+
+impl<A: Accumulator> Machine<A> {
+    pub fn with_instructions(program: Vec<Instruction>, instructions: InstructionSet<A>) -> Self {
+        Machine {
+            program,
+            instructions,
+            regs: Registers::default(),
+            overflow: Overflow::default(),
+            steps: 0,
+            step_limit: None,
+            trace: None,
+        }
+    }
+
+    /// What to do when the accumulator overflows. The default is `Overflow::Error`.
+    pub fn with_overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Records every instruction the machine runs, for `trace` to return.
+    pub fn with_trace(mut self) -> Self {
+        self.trace = Some(Trace::default());
+        self
+    }
+
+    /// Everything run so far, if the machine was made `with_trace`.
+    pub fn trace(&self) -> Option<&Trace<A>> {
+        self.trace.as_ref()
+    }
+
+    /// Stops the machine with `Halt::StepLimit` once it has run `limit` instructions.
+    pub fn with_step_limit(mut self, limit: u64) -> Self {
+        self.step_limit = Some(limit);
+        self
+    }
+
+    pub fn program(&self) -> &[Instruction] {
+        &self.program
+    }
+
+    pub fn registers(&self) -> Registers<A> {
+        self.regs
+    }
+
+    pub fn pc(&self) -> i64 {
+        self.regs.pc
+    }
+
+    pub fn acc(&self) -> A {
+        self.regs.acc
+    }
+
+    /// How many instructions have been run so far.
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// The instruction at the program counter, if it's inside the program.
+    pub fn current(&self) -> Option<Instruction> {
+        if self.regs.pc < 0 {
+            return None;
+        }
+        self.program.get(self.regs.pc as usize).copied()
+    }
+
+    /// Why the machine can't run another instruction, if it can't.
+    pub fn halted(&self) -> Option<Halt> {
+        let len = self.program.len() as i64;
+        let pc = self.regs.pc;
+        if pc == len {
+            Some(Halt::Terminated)
+        } else if pc < 0 {
+            Some(Halt::PcBeforeStart { pc })
+        } else if pc > len {
+            Some(Halt::PcPastEnd { pc })
+        } else if self.step_limit.map(|limit| self.steps >= limit) == Some(true) {
+            Some(Halt::StepLimit)
+        } else {
+            None
+        }
+    }
+
+    /// Runs the instruction at the program counter, or gives the reason it can't.
+    pub fn step(&mut self) -> Result<(), Halt> {
+        if let Some(halt) = self.halted() {
+            return Err(halt);
+        }
+        let pc = self.regs.pc;
+        let instr = self.program[pc as usize];
+        self.instructions
+            .execute(instr, &mut self.regs, self.overflow)?;
+        self.steps += 1;
+        if let Some(trace) = &mut self.trace {
+            trace.entries.push(TraceEntry {
+                pc,
+                opcode: instr.opcode(),
+                arg: instr.arg(),
+                acc: self.regs.acc,
+            });
+        }
+        Ok(())
+    }
+
+    /// Runs until the machine halts.
+    pub fn run(&mut self) -> Halt {
+        loop {
+            if let Err(halt) = self.step() {
+                return halt;
+            }
+        }
+    }
+
+    /// Runs until the machine halts, or is about to run an instruction for the second time.
+    /// That always means an infinite loop for the built-in opcodes, but registered ones might
+    /// behave differently on a second visit.
+    pub fn run_until_repeat(&mut self) -> Halt {
+        let mut visited = HashSet::new();
+        loop {
+            if self.halted().is_none() && !visited.insert(self.regs.pc) {
+                return Halt::Loop { pc: self.regs.pc };
+            }
+            if let Err(halt) = self.step() {
+                return halt;
+            }
+        }
+    }
+}
+
+/// One instruction that was run: where it was, what it was, and the accumulator after it ran.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub struct TraceEntry<A = i32> {
+    pub pc: i64,
+    pub opcode: &'static str,
+    pub arg: i32,
+    pub acc: A,
+}
+
+/// Every instruction a machine ran, in order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Trace<A = i32> {
+    pub entries: Vec<TraceEntry<A>>,
+}
+
+impl<A> Default for Trace<A> {
+    fn default() -> Self {
+        Trace { entries: vec![] }
+    }
+}
+
+impl<A: Accumulator> Trace<A> {
+    /// One line per instruction, e.g. `     3  jmp -3      acc=1`.
+    pub fn to_text(&self) -> String {
+        self.entries
+            .iter()
+            .map(|e| format!("{:6}  {} {:<+8} acc={}\n", e.pc, e.opcode, e.arg, e.acc))
+            .collect()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.entries).unwrap()
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Breakpoint<A = i32> {
+    /// Stop before running the instruction at this pc.
+    Pc(i64),
+    /// Stop after any instruction that changes the accumulator to this value.
+    Acc(A),
+}
+
+impl<A: Accumulator> Breakpoint<A> {
+    /// Parses `12` (a pc) or `acc=-4`.
+    pub fn parse(s: &str) -> Option<Breakpoint<A>> {
+        match s.strip_prefix("acc=") {
+            Some(acc) => acc.parse().ok().map(Breakpoint::Acc),
+            None => s.parse().ok().map(Breakpoint::Pc),
+        }
+    }
+}
+
+impl<A: Accumulator> fmt::Display for Breakpoint<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Breakpoint::Pc(pc) => write!(f, "pc {}", pc),
+            Breakpoint::Acc(acc) => write!(f, "acc={}", acc),
+        }
+    }
+}
+
+/// Why the debugger handed control back.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Stop<A = i32> {
+    Breakpoint(Breakpoint<A>),
+    /// Includes `Halt::Loop`: the debugger always watches for the program repeating itself,
+    /// so continuing through day 8's loop stops rather than running forever.
+    Halted(Halt),
+}
+
+/// Single-steps or continues a machine, stopping at breakpoints.
+#[derive(Debug, Clone)]
+pub struct Debugger<A = i32> {
+    machine: Machine<A>,
+    breakpoints: Vec<Breakpoint<A>>,
+    visited: HashSet<i64>,
+}
+
+impl<A: Accumulator> Debugger<A> {
+    pub fn new(machine: Machine<A>) -> Self {
+        Debugger {
+            machine,
+            breakpoints: vec![],
+            visited: HashSet::new(),
+        }
+    }
+
+    pub fn machine(&self) -> &Machine<A> {
+        &self.machine
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint<A>] {
+        &self.breakpoints
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint<A>) {
+        if !self.breakpoints.contains(&breakpoint) {
+            self.breakpoints.push(breakpoint);
+        }
+    }
+
+    /// Gives whether there was such a breakpoint to remove.
+    pub fn remove_breakpoint(&mut self, breakpoint: Breakpoint<A>) -> bool {
+        let before = self.breakpoints.len();
+        self.breakpoints.retain(|&b| b != breakpoint);
+        self.breakpoints.len() != before
+    }
+
+    /// Runs exactly one instruction, ignoring breakpoints, even one that's been run before.
+    pub fn step(&mut self) -> Result<(), Stop<A>> {
+        let pc = self.machine.pc();
+        self.machine.step().map_err(Stop::Halted)?;
+        self.visited.insert(pc);
+        Ok(())
+    }
+
+    /// Runs until a breakpoint is hit or the machine halts. Like `Machine::run_until_repeat`,
+    /// it also stops with `Halt::Loop` before running any instruction for a second time
+    /// (counting the ones stepped through). Otherwise it always runs at least one
+    /// instruction, so continuing from a pc breakpoint doesn't just stop there again.
+    pub fn resume(&mut self) -> Stop<A> {
+        loop {
+            let pc = self.machine.pc();
+            if self.machine.halted().is_none() && self.visited.contains(&pc) {
+                return Stop::Halted(Halt::Loop { pc });
+            }
+            let before = self.machine.acc();
+            if let Err(stop) = self.step() {
+                return stop;
+            }
+            let acc = self.machine.acc();
+            if acc != before && self.breakpoints.contains(&Breakpoint::Acc(acc)) {
+                return Stop::Breakpoint(Breakpoint::Acc(acc));
+            }
+            let pc = self.machine.pc();
+            if self.breakpoints.contains(&Breakpoint::Pc(pc)) {
+                return Stop::Breakpoint(Breakpoint::Pc(pc));
+            }
+        }
+    }
+}
+
+/// Swapping one `jmp` for a `nop` or the other way round, as in day 8 part 2.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Flip {
+    pub pc: usize,
+    pub from: Instruction,
+    pub to: Instruction,
+}
+
+impl Flip {
+    /// A copy of `program` with the flip made.
+    pub fn apply(&self, program: &[Instruction]) -> Vec<Instruction> {
+        let mut program = program.to_vec();
+        program[self.pc] = self.to;
+        program
+    }
+}
+
+fn flipped(instr: Instruction) -> Option<Instruction> {
+    match instr {
+        Instruction::Jmp(arg) => Some(Instruction::Nop(arg)),
+        Instruction::Nop(arg) => Some(Instruction::Jmp(arg)),
+        _ => None,
+    }
+}
+
+/// Where the instruction at `pc` sends the program counter. Registered opcodes are assumed
+/// to fall through to the next instruction, since there's no telling what they really do.
+fn successor(pc: usize, instr: Instruction) -> i64 {
+    match instr {
+        Instruction::Jmp(arg) => pc as i64 + arg as i64,
+        _ => pc as i64 + 1,
+    }
+}
+
+/// The control flow graph of a program, and which instructions lead to it terminating.
+///
+/// Without registered opcodes, a program's control flow doesn't depend on the accumulator,
+/// so each instruction has exactly one successor. Walking the edges backwards from the end of
+/// the program finds every pc that terminates. Flipping an instruction on the path the program
+/// actually runs fixes it when the flipped instruction sends it to one of those pcs, and the
+/// way from there to the end doesn't come back through the flipped instruction (which would
+/// now send it round again). Both passes are linear, where trying every flip and rerunning the
+/// program is quadratic.
+#[derive(Debug, Clone)]
+pub struct ControlFlow<'a> {
+    program: &'a [Instruction],
+    /// Indexed by pc, with one extra entry for the end of the program.
+    terminates: Vec<bool>,
+    /// The pcs the program runs, in order, as `executed_path` gives them.
+    path: Vec<usize>,
+    /// For each pc that terminates, the index in `path` where its way to the end first joins
+    /// the executed path, or `path.len()` if it never does. Indexed like `terminates`.
+    joins_path_at: Vec<usize>,
+}
+
+impl<'a> ControlFlow<'a> {
+    pub fn new(program: &'a [Instruction]) -> Self {
+        let len = program.len();
+        let mut predecessors = vec![vec![]; len + 1];
+        for (pc, &instr) in program.iter().enumerate() {
+            let next = successor(pc, instr);
+            if (0..=len as i64).contains(&next) {
+                predecessors[next as usize].push(pc);
+            }
+        }
+        let mut path = vec![];
+        let mut position = vec![None; len];
+        let mut pc = 0;
+        while (0..len as i64).contains(&pc) && position[pc as usize].is_none() {
+            position[pc as usize] = Some(path.len());
+            path.push(pc as usize);
+            pc = successor(pc as usize, program[pc as usize]);
+        }
+
+        let mut terminates = vec![false; len + 1];
+        let mut joins_path_at = vec![path.len(); len + 1];
+        terminates[len] = true;
+        let mut todo = vec![len];
+        while let Some(pc) = todo.pop() {
+            for &prev in &predecessors[pc] {
+                if !terminates[prev] {
+                    terminates[prev] = true;
+                    joins_path_at[prev] = position[prev].unwrap_or(joins_path_at[pc]);
+                    todo.push(prev);
+                }
+            }
+        }
+        ControlFlow {
+            program,
+            terminates,
+            path,
+            joins_path_at,
+        }
+    }
+
+    /// Whether a program counter of `pc` leads to the program ending normally.
+    pub fn terminates(&self, pc: i64) -> bool {
+        (0..self.terminates.len() as i64).contains(&pc) && self.terminates[pc as usize]
+    }
+
+    /// The pcs of the instructions the program runs, in order, up to it terminating, jumping
+    /// out of bounds, or repeating an instruction.
+    pub fn executed_path(&self) -> Vec<usize> {
+        self.path.clone()
+    }
+
+    /// Every flip of an instruction the program runs that makes it terminate. If the program
+    /// terminates already, flipping an instruction it never runs keeps it that way, but those
+    /// aren't included.
+    pub fn terminating_flips(&self) -> impl Iterator<Item = Flip> + '_ {
+        self.path.iter().enumerate().filter_map(move |(i, &pc)| {
+            let from = self.program[pc];
+            let to = flipped(from)?;
+            let next = successor(pc, to);
+            // Everything on the path before `pc` is still the same, so it's only a problem to
+            // get back to the path at `pc` or later.
+            if self.terminates(next) && self.joins_path_at[next as usize] > i {
+                Some(Flip { pc, from, to })
+            } else {
+                None
+            }
+        })
+    }
+
+    /// The first flip along the program's path that makes it terminate.
+    pub fn repair(&self) -> Option<Flip> {
+        self.terminating_flips().next()
+    }
+
+    /// Which pcs are on a cycle, i.e. lead back to themselves. A pc that only leads into a
+    /// cycle runs forever too, but isn't marked.
+    pub fn cycles(&self) -> Vec<bool> {
+        let len = self.program.len();
+        // 0: not looked at yet, 1: on the path being followed, 2: done
+        let mut state = vec![0u8; len];
+        let mut on_cycle = vec![false; len];
+        for start in 0..len {
+            let mut path = vec![];
+            let mut pc = start as i64;
+            while (0..len as i64).contains(&pc) && state[pc as usize] == 0 {
+                state[pc as usize] = 1;
+                path.push(pc as usize);
+                pc = successor(pc as usize, self.program[pc as usize]);
+            }
+            if (0..len as i64).contains(&pc) && state[pc as usize] == 1 {
+                let from = path.iter().position(|&p| p as i64 == pc).unwrap();
+                path[from..].iter().for_each(|&p| on_cycle[p] = true);
+            }
+            path.iter().for_each(|&p| state[p] = 2);
+        }
+        on_cycle
+    }
+}
+
+/// A program in the format `InstructionSet::parse` reads, one instruction per line.
+pub fn disassemble(program: &[Instruction]) -> String {
+    program.iter().map(|instr| format!("{}\n", instr)).collect()
+}
+
+/// A program laid out for reading, in the format `InstructionSet::assemble` reads. Every jump
+/// target gets a label (`L12` for pc 12, and jumps use it), and comments mark instructions
+/// that are on an infinite loop or that the program never gets to.
+pub fn listing(program: &[Instruction]) -> String {
+    let cfg = ControlFlow::new(program);
+    let len = program.len();
+    let target = |pc: usize, instr: Instruction| match instr {
+        Instruction::Jmp(_) => Some(successor(pc, instr)).filter(|t| (0..=len as i64).contains(t)),
+        _ => None,
+    };
+    let mut is_target = vec![false; len + 1];
+    for (pc, &instr) in program.iter().enumerate() {
+        if let Some(t) = target(pc, instr) {
+            is_target[t as usize] = true;
+        }
+    }
+    let mut reached = vec![false; len];
+    cfg.executed_path()
+        .into_iter()
+        .for_each(|pc| reached[pc] = true);
+    let on_cycle = cfg.cycles();
+
+    let mut out = String::new();
+    for (pc, &instr) in program.iter().enumerate() {
+        let label = if is_target[pc] {
+            format!("L{}:", pc)
+        } else {
+            String::new()
+        };
+        let code = match target(pc, instr) {
+            Some(t) => format!("{} L{}", instr.opcode(), t),
+            None => instr.to_string(),
+        };
+        let notes: Vec<_> = [
+            (on_cycle[pc], "loops forever"),
+            (!reached[pc], "unreachable"),
+        ]
+        .iter()
+        .filter(|(applies, _)| *applies)
+        .map(|(_, note)| *note)
+        .collect();
+        let line = format!("{:<8}{:<12}", label, code);
+        if notes.is_empty() {
+            out += line.trim_end();
+        } else {
+            out += &format!("{}# {}", line, notes.join(", "));
+        }
+        out.push('\n');
+    }
+    if is_target[len] {
+        out += &format!("L{}:\n", len);
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn run(program: &str) -> (Halt, i32) {
+        let mut machine = Machine::new(InstructionSet::new().parse(program).unwrap());
+        let halt = machine.run_until_repeat();
+        (halt, machine.acc())
+    }
+
+    #[test]
+    fn test_parse() {
+        let set = InstructionSet::new();
+        assert_eq!(
+            set.parse("acc +1\njmp -4\nnop +0").unwrap(),
+            vec![
+                Instruction::Acc(1),
+                Instruction::Jmp(-4),
+                Instruction::Nop(0)
+            ]
+        );
+        let err = set.parse("acc +1\nmul +2").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+        assert!(err.message.contains("unknown opcode \"mul\""), "{}", err);
+        let err = set.parse("acc x").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 5));
+    }
+
+    #[test]
+    fn test_halts() {
+        assert_eq!(run("acc +1\nacc +2"), (Halt::Terminated, 3));
+        assert_eq!(run("acc +1\njmp -1"), (Halt::Loop { pc: 0 }, 1));
+        assert_eq!(run("acc +1\njmp -5"), (Halt::PcBeforeStart { pc: -4 }, 1));
+        assert_eq!(run("jmp +3\nacc +1"), (Halt::PcPastEnd { pc: 3 }, 0));
+
+        let program = InstructionSet::new().parse("acc +1\njmp -1").unwrap();
+        let mut machine = Machine::new(program).with_step_limit(10);
+        assert_eq!(machine.run(), Halt::StepLimit);
+        assert_eq!((machine.steps(), machine.acc()), (10, 5));
+    }
+
+    #[test]
+    fn test_custom_opcode() {
+        let mut set = InstructionSet::new();
+        set.register("mul", |regs, arg, _| {
+            regs.acc = regs.acc.checked_mul(arg).ok_or(AccOverflow)?;
+            regs.pc += 1;
+            Ok(())
+        });
+        let program = set.parse("acc +3\nmul +7\nmul -1").unwrap();
+        assert_eq!(program[1], Instruction::Custom("mul", 7));
+        let mut machine = Machine::with_instructions(program, set.clone());
+        assert_eq!(machine.run(), Halt::Terminated);
+        assert_eq!(machine.acc(), -21);
+
+        // Parsed with one set, but run with another that doesn't have "mul"
+        let program = set.parse("acc +3\nmul +7").unwrap();
+        let mut machine = Machine::new(program);
+        assert_eq!(machine.run(), Halt::UnknownOpcode { pc: 1 });
+        assert_eq!(machine.acc(), 3);
+    }
+
+    #[test]
+    fn test_trace() {
+        let program = InstructionSet::new()
+            .parse("acc +1\njmp +2\nacc +5\nacc -3")
+            .unwrap();
+        let mut machine = Machine::new(program).with_trace();
+        assert_eq!(machine.run(), Halt::Terminated);
+        let trace = machine.trace().unwrap();
+        let steps: Vec<_> = trace.entries.iter().map(|e| (e.pc, e.acc)).collect();
+        assert_eq!(steps, vec![(0, 1), (1, 1), (3, -2)]);
+        assert_eq!(
+            trace.to_text().lines().nth(1),
+            Some("     1  jmp +2       acc=1")
+        );
+        assert!(trace.to_json().contains(
+            r#""pc": 3,
+    "opcode": "acc",
+    "arg": -3,
+    "acc": -2"#
+        ));
+    }
+
+    #[test]
+    fn test_debugger() {
+        let program = InstructionSet::new().parse(crate::day8::EXAMPLE).unwrap();
+        let mut debugger = Debugger::new(Machine::new(program));
+        debugger.add_breakpoint(Breakpoint::Pc(4));
+        debugger.add_breakpoint(Breakpoint::parse("acc=2").unwrap());
+        assert_eq!(debugger.resume(), Stop::Breakpoint(Breakpoint::Acc(2)));
+        assert_eq!(debugger.machine().pc(), 7);
+        assert_eq!(debugger.resume(), Stop::Breakpoint(Breakpoint::Pc(4)));
+        assert_eq!(debugger.machine().acc(), 5);
+        // Stepping ignores breakpoints
+        debugger.step().unwrap();
+        assert_eq!(debugger.machine().pc(), 1);
+        assert!(debugger.remove_breakpoint(Breakpoint::Acc(2)));
+        assert!(!debugger.remove_breakpoint(Breakpoint::Acc(2)));
+        assert_eq!(debugger.resume(), Stop::Halted(Halt::Loop { pc: 1 }));
+        assert_eq!(debugger.machine().acc(), 5);
+        // Stepping carries on round the loop, through pcs that have already been run
+        debugger.step().unwrap();
+        assert_eq!((debugger.machine().pc(), debugger.machine().acc()), (2, 6));
+        debugger.step().unwrap();
+        assert_eq!(debugger.machine().pc(), 6);
+        assert_eq!(debugger.resume(), Stop::Halted(Halt::Loop { pc: 6 }));
+    }
+
+    #[test]
+    fn test_repair() {
+        let program = InstructionSet::new().parse(crate::day8::EXAMPLE).unwrap();
+        let cfg = ControlFlow::new(&program);
+        assert_eq!(cfg.executed_path(), vec![0, 1, 2, 6, 7, 3, 4]);
+        assert!(cfg.terminates(8) && cfg.terminates(9) && !cfg.terminates(7));
+        let flips: Vec<_> = cfg.terminating_flips().collect();
+        assert_eq!(
+            flips,
+            vec![Flip {
+                pc: 7,
+                from: Instruction::Jmp(-4),
+                to: Instruction::Nop(-4)
+            }]
+        );
+        assert_eq!(flips, slow_flips(&program));
+
+        // When the program terminates already, most flips break it instead
+        let program = InstructionSet::new()
+            .parse("nop +0\nacc +1\njmp +2\nacc +5\nnop -3\nnop +1")
+            .unwrap();
+        let cfg = ControlFlow::new(&program);
+        let flips: Vec<_> = cfg.terminating_flips().collect();
+        assert_eq!(
+            flips,
+            vec![
+                Flip {
+                    pc: 2,
+                    from: Instruction::Jmp(2),
+                    to: Instruction::Nop(2)
+                },
+                Flip {
+                    pc: 5,
+                    from: Instruction::Nop(1),
+                    to: Instruction::Jmp(1)
+                }
+            ]
+        );
+        assert_eq!(flips, slow_flips(&program));
+        let program = InstructionSet::new().parse("nop +0\nacc +1").unwrap();
+        assert_eq!(ControlFlow::new(&program).repair(), None);
+    }
+
+    /// Tries flipping each instruction the program runs, and runs the result.
+    fn slow_flips(program: &[Instruction]) -> Vec<Flip> {
+        let mut flips = vec![];
+        for pc in ControlFlow::new(program).executed_path() {
+            if let Some(to) = flipped(program[pc]) {
+                let flip = Flip {
+                    pc,
+                    from: program[pc],
+                    to,
+                };
+                if Machine::new(flip.apply(program))
+                    .run_until_repeat()
+                    .is_terminated()
+                {
+                    flips.push(flip);
+                }
+            }
+        }
+        flips
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let set = InstructionSet::new();
+        let program = set.parse(crate::day8::EXAMPLE).unwrap();
+        assert_eq!(disassemble(&program).trim_end(), crate::day8::EXAMPLE);
+        assert_eq!(Instruction::Jmp(-4).to_string(), "jmp -4");
+        assert_eq!(set.assemble(&listing(&program)).unwrap(), program);
+    }
+
+    #[test]
+    fn test_assemble() {
+        let set = InstructionSet::new();
+        let program = set
+            .assemble(
+                "# counts down from 3
+                start:  acc +3
+                loop:   acc -1   # one fewer
+                        jmp loop
+                        jmp end
+                end:",
+            )
+            .unwrap();
+        assert_eq!(
+            program,
+            vec![
+                Instruction::Acc(3),
+                Instruction::Acc(-1),
+                Instruction::Jmp(-1),
+                Instruction::Jmp(1)
+            ]
+        );
+        let err = set.assemble("acc +1\njmp nowhere").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 5));
+        assert!(err.message.contains("unknown label"), "{}", err);
+        let err = set.assemble("a: nop +0\n  a: nop +0").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 3));
+        assert!(set.assemble("1x: nop +0").is_err());
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 3: label \"a\" is defined twice"
+        );
+        assert_eq!(err.into_parse_error(23).day, 23);
+    }
+
+    #[test]
+    fn test_listing() {
+        let program = InstructionSet::new().parse(crate::day8::EXAMPLE).unwrap();
+        let expected = "
+        nop +0
+L1:     acc +1      # loops forever
+        jmp L6      # loops forever
+L3:     acc +3      # loops forever
+        jmp L1      # loops forever
+        acc -99     # unreachable
+L6:     acc +1      # loops forever
+        jmp L3      # loops forever
+        acc +6      # unreachable
+";
+        assert_eq!(listing(&program), &expected[1..]);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let program = InstructionSet::new()
+            .parse("acc +2147483647\nacc +1\nacc +1")
+            .unwrap();
+        let mut machine = Machine::new(program.clone());
+        assert_eq!(machine.run(), Halt::Overflow { pc: 1 });
+        assert_eq!((machine.pc(), machine.acc()), (1, i32::MAX));
+
+        let mut machine = Machine::new(program.clone()).with_overflow(Overflow::Wrap);
+        assert_eq!(machine.run(), Halt::Terminated);
+        assert_eq!(machine.acc(), i32::MIN + 1);
+
+        let mut machine = Machine::new(program.clone()).with_overflow(Overflow::Saturate);
+        assert_eq!(machine.run(), Halt::Terminated);
+        assert_eq!(machine.acc(), i32::MAX);
+
+        let mut machine = Machine::<i64>::with_instructions(program, InstructionSet::default());
+        assert_eq!(machine.run(), Halt::Terminated);
+        assert_eq!(machine.acc(), i32::MAX as i64 + 2);
+    }
+}
@@ -1,43 +1,77 @@
-use std::collections::HashMap;
-use std::collections::HashSet;
+use crate::error::{ParseError, Source};
+use crate::example::Example;
+use crate::expenses::ExpenseReport;
+use crate::solution::Solution;
+
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: EXAMPLE,
+        expected: "514579",
+    },
+    Example {
+        part: 2,
+        input: EXAMPLE,
+        expected: "241861950",
+    },
+];
+
+const EXAMPLE: &str = "1721
+979
+366
+299
+675
+1456";
 
 #[aoc_generator(day1)]
-pub fn day1_parse(input: &str) -> Vec<i32> {
+pub fn day1_parse(input: &str) -> Result<Vec<i32>, ParseError> {
+    let src = Source::new(1, input);
     let mut ret = vec![];
     for line in input.lines() {
-        ret.push(line.parse().expect("parses as int"));
+        ret.push(src.number(line)?);
     }
-    ret
+    if ret.is_empty() {
+        return Err(src.eof("the expense report is empty"));
+    }
+    Ok(ret)
+}
+
+/// The product of the first `k` entries that add up to 2020, or `None` if no `k` entries do.
+/// These used to be a hash set lookup for two entries and a table of pairwise sums for three;
+/// `expenses` does any number.
+fn solve(vals: &[i32], k: usize) -> Option<i64> {
+    let report = ExpenseReport::new(vals.iter().map(|&v| v.into()).collect());
+    let selection = report.find(k, 2020)?;
+    debug!("{:?} add up to 2020", selection.values);
+    Some(selection.product())
 }
 
 #[aoc(day1, part1)]
-pub fn day1_solve(vals: &[i32]) -> i32 {
-    let mut set = HashSet::new();
-    for t in vals {
-        if set.contains(&(2020 - t)) {
-            return t * (2020 - t);
-        } else {
-            set.insert(t);
-        }
-    }
-    panic!()
+pub fn day1_solve(vals: &[i32]) -> Option<i64> {
+    solve(vals, 2)
 }
 
 #[aoc(day1, part2)]
-pub fn day1_solve2(vals: &[i32]) -> i32 {
-    let mut twofolds = HashMap::new();
-    for t1 in vals {
-        for t2 in vals {
-            if t1 + t2 < 2020 {
-                twofolds.insert(t1 + t2, t1 * t2);
-            }
-        }
+pub fn day1_solve2(vals: &[i32]) -> Option<i64> {
+    solve(vals, 3)
+}
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    type Input = Vec<i32>;
+    type Part1 = Option<i64>;
+    type Part2 = Option<i64>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        day1_parse(input)
     }
-    for z in vals {
-        match twofolds.get(&(2020 - z)) {
-            Some(product) => return product * z,
-            None => continue,
-        }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        day1_solve(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        day1_solve2(input)
     }
-    panic!()
 }
@@ -1,40 +1,128 @@
-use std::collections::HashMap;
+use num_bigint::BigUint;
+
+use crate::adapters::{AdapterChain, Rules};
+use crate::error::{ParseError, Source};
+use crate::example::Example;
+use crate::solution::Solution;
+
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: SMALL_EXAMPLE,
+        expected: "35",
+    },
+    Example {
+        part: 2,
+        input: SMALL_EXAMPLE,
+        expected: "8",
+    },
+    Example {
+        part: 1,
+        input: LARGE_EXAMPLE,
+        expected: "220",
+    },
+    Example {
+        part: 2,
+        input: LARGE_EXAMPLE,
+        expected: "19208",
+    },
+];
+
+const SMALL_EXAMPLE: &str = "16\n10\n15\n5\n1\n11\n7\n19\n6\n12\n4";
+
+const LARGE_EXAMPLE: &str = "28\n33\n18\n42\n31\n14\n46\n20\n48\n47\n24\n23\n49\n45\n19\n38\n39\n11\n1\n32\n25\n35\n8\n17\n7\n9\n4\n2\n34\n10\n3";
 
 #[aoc_generator(day10)]
-pub fn gen(input: &str) -> Vec<i64> {
-    input.lines().map(|x| x.parse().unwrap()).collect()
+pub fn gen(input: &str) -> Result<Vec<i64>, ParseError> {
+    let src = Source::new(10, input);
+    let adapters: Vec<i64> = input
+        .lines()
+        .map(|x| match src.number(x)? {
+            rating if rating > 0 => Ok(rating),
+            rating => Err(src.error(
+                x,
+                format!("adapters must be rated above 0 jolts, not {}", rating),
+            )),
+        })
+        .collect::<Result<_, _>>()?;
+    if adapters.is_empty() {
+        return Err(src.eof("there are no adapters"));
+    }
+    Ok(adapters)
 }
 
+/// `None` if the adapters can't all be used in one chain.
 #[aoc(day10, part1)]
-pub fn day10(input: &[i64]) -> i64 {
-    let mut input = input.to_owned();
-    input.sort_unstable();
-    let (ones, threes, _) = input.iter().fold((0, 0, 0), |(ones, threes, prev), next| {
-        if next - prev == 1 {
-            (ones + 1, threes, *next)
-        } else if next - prev == 3 {
-            (ones, threes + 1, *next)
-        } else {
-            (ones, threes, *next)
+pub fn day10(input: &[i64]) -> Option<usize> {
+    let histogram = match AdapterChain::new(input, Rules::default()).map(|c| c.histogram()) {
+        Ok(Ok(histogram)) => histogram,
+        Ok(Err(e)) => {
+            debug!("the adapters don't chain: {}", e);
+            return None;
+        }
+        Err(e) => {
+            debug!("{}", e);
+            return None;
         }
-    });
-    ones * (threes + 1)
+    };
+    debug!("gaps: {:?}", histogram);
+    Some(histogram.get(&1).unwrap_or(&0) * histogram.get(&3).unwrap_or(&0))
 }
 
 /// Dynamic programmic: sort the input and iterate, computing for each adapter the number
-/// of chains ending with that adapter. Our answer is the number of ways for the built-in adapter
+/// of chains from that adapter to the device. `AdapterChain` does this now, with a `BigUint`
+/// since the counts outgrow an `i64` on longer chains.
 #[aoc(day10, part2)]
-pub fn day10_2(input: &[i64]) -> i64 {
-    let mut input = input.to_owned();
-    input.sort_unstable();
-    input.push(input[input.len() - 1] + 3);
-    let mut n_ways: HashMap<i64, i64> = HashMap::new();
-    n_ways.insert(0, 1);
-    for &adapter in input.iter() {
-        let sum = n_ways.get(&(adapter - 3)).cloned().unwrap_or(0)
-            + n_ways.get(&(adapter - 2)).cloned().unwrap_or(0)
-            + n_ways.get(&(adapter - 1)).cloned().unwrap_or(0);
-        n_ways.insert(adapter, sum);
+pub fn day10_2(input: &[i64]) -> Option<BigUint> {
+    match AdapterChain::new(input, Rules::default()) {
+        Ok(chain) => Some(chain.arrangements()),
+        Err(e) => {
+            debug!("{}", e);
+            None
+        }
+    }
+}
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    type Input = Vec<i64>;
+    type Part1 = Option<usize>;
+    type Part2 = Option<BigUint>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        gen(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        day10(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        day10_2(input)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_gen() {
+        assert_eq!(gen("3\n1\n2").unwrap(), vec![3, 1, 2]);
+        let err = gen("3\n0\n2").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+        assert!(err.message.contains("not 0"), "{}", err.message);
+        assert!(gen("-4").is_err());
+    }
+
+    #[test]
+    fn test_no_answer() {
+        // A gap of 4 breaks the chain
+        assert_eq!(day10(&[1, 5]), None);
+        assert_eq!(day10(&[1, 4]), Some(2));
+        // gen won't give these back, but the solvers are public
+        assert_eq!(day10(&[0, 3]), None);
+        assert_eq!(day10_2(&[-1]), None);
     }
-    n_ways.get(&input[input.len() - 1]).cloned().unwrap()
 }
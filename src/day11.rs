@@ -8,6 +8,34 @@ pub enum Square {
 use std::convert::{TryFrom, TryInto};
 use Square::*;
 
+use crate::error::{ParseError, Source};
+use crate::example::Example;
+use crate::solution::Solution;
+
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: EXAMPLE,
+        expected: "37",
+    },
+    Example {
+        part: 2,
+        input: EXAMPLE,
+        expected: "26",
+    },
+];
+
+const EXAMPLE: &str = "L.LL.LL.LL
+LLLLLLL.LL
+L.L.L..L..
+LLLL.LL.LL
+L.LL.LL.LL
+L.LLLLL.LL
+..L.L.....
+LLLLLLLLLL
+L.LLLLLL.L
+L.LLLLL.LL";
+
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct Layout {
     grid: Vec<Vec<Square>>,
@@ -78,7 +106,8 @@ impl Layout {
         *sq
     }
 
-    fn step(&self) -> Layout {
+    /// One round of the part 1 rules, where people look at the 8 seats right next to them.
+    pub fn step(&self) -> Layout {
         let mut new_grid = vec![];
         for (row_num, row) in self.grid.iter().enumerate() {
             let mut new_row = vec![];
@@ -99,6 +128,25 @@ impl Layout {
 }
 
 impl Layout {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Square {
+        self.grid[row][col]
+    }
+
+    pub fn occupied(&self) -> usize {
+        self.grid
+            .iter()
+            .map(|v| v.iter().filter(|&sq| *sq == Occupied).count())
+            .sum()
+    }
+
     // (seat, occupied)
     fn neighbor_counts_part2(&self, row: i32, col: i32) -> (usize, usize) {
         let mut ret = (0, 0);
@@ -156,7 +204,9 @@ impl Layout {
         *sq
     }
 
-    fn step_part2(&self) -> Layout {
+    /// One round of the part 2 rules, where people look at the first seat they can see
+    /// in each of the 8 directions.
+    pub fn step_part2(&self) -> Layout {
         let mut new_grid = vec![];
         for (row_num, row) in self.grid.iter().enumerate() {
             let mut new_row = vec![];
@@ -179,38 +229,37 @@ impl Layout {
 }
 
 #[aoc_generator(day11)]
-pub fn parse(input: &str) -> Layout {
-    //     let input = "L.LL.LL.LL
-    // LLLLLLL.LL
-    // L.L.L..L..
-    // LLLL.LL.LL
-    // L.LL.LL.LL
-    // L.LLLLL.LL
-    // ..L.L.....
-    // LLLLLLLLLL
-    // L.LLLLLL.L
-    // L.LLLLL.LL";
-
+pub fn parse(input: &str) -> Result<Layout, ParseError> {
+    let src = Source::new(11, input);
     let mut grid: Vec<Vec<Square>> = vec![];
     for line in input.lines() {
         let row: Vec<Square> = line
-            .chars()
-            .map(|c| match c {
-                'L' => Seat,
-                '.' => Floor,
-                '#' => Occupied,
-                x => panic!("bad char {}", x),
+            .char_indices()
+            .map(|(i, c)| match c {
+                'L' => Ok(Seat),
+                '.' => Ok(Floor),
+                '#' => Ok(Occupied),
+                x => Err(src.error(
+                    &line[i..],
+                    format!("expected 'L', '.' or '#', found {:?}", x),
+                )),
             })
-            .collect();
+            .collect::<Result<_, _>>()?;
+        if !grid.is_empty() && row.len() != grid[0].len() {
+            return Err(src.error(line, "all rows of the layout must be the same width"));
+        }
         grid.push(row);
     }
+    if grid.is_empty() {
+        return Err(src.eof("the layout is empty"));
+    }
     let width = grid[0].len();
     let height = grid.len();
-    Layout {
+    Ok(Layout {
         grid,
         width,
         height,
-    }
+    })
 }
 
 #[aoc(day11, part1)]
@@ -224,11 +273,7 @@ pub fn day1(input: &Layout) -> usize {
         }
         layout = new_layout;
     }
-    layout
-        .grid
-        .into_iter()
-        .map(|v| v.iter().filter(|&sq| *sq == Occupied).count())
-        .sum()
+    layout.occupied()
 }
 
 #[aoc(day11, part2)]
@@ -242,9 +287,25 @@ pub fn day2(input: &Layout) -> usize {
         }
         layout = new_layout;
     }
-    layout
-        .grid
-        .into_iter()
-        .map(|v| v.iter().filter(|&sq| *sq == Occupied).count())
-        .sum()
+    layout.occupied()
+}
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    type Input = Layout;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        day1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        day2(input)
+    }
 }
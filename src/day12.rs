@@ -1,5 +1,28 @@
 use std::convert::{TryFrom, TryInto};
 
+use crate::error::{ParseError, Source};
+use crate::example::Example;
+use crate::solution::Solution;
+
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: EXAMPLE,
+        expected: "25",
+    },
+    Example {
+        part: 2,
+        input: EXAMPLE,
+        expected: "286",
+    },
+];
+
+const EXAMPLE: &str = "F10
+N3
+F7
+R90
+F11";
+
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
 pub enum Instr {
     F(i32),
@@ -27,6 +50,9 @@ impl TryFrom<&str> for Instr {
         match value.chars().next().unwrap() {
             'F' => Ok(Instr::F(body)),
 
+            'L' | 'R' if ![90, 180, 270].contains(&body) => {
+                Err(format!("unsupported rotation {}", body))
+            }
             'L' => Ok(Instr::L(body)),
             'R' => Ok(Instr::R(body)),
 
@@ -41,21 +67,25 @@ impl TryFrom<&str> for Instr {
 }
 
 #[aoc_generator(day12)]
-pub fn parse(input: &str) -> Vec<Instr> {
-    input.lines().map(|line| line.try_into().unwrap()).collect()
+pub fn parse(input: &str) -> Result<Vec<Instr>, ParseError> {
+    let src = Source::new(12, input);
+    input
+        .lines()
+        .map(|line| line.try_into().map_err(|e| src.error(line, e)))
+        .collect()
 }
 
 /// We use standard coordinates with North = Up = positive y direction
 /// Heading is a unit vector in the direction we're facing i.e. east is (1, 0)
 #[derive(Debug)]
-struct State {
-    x: i32,
-    y: i32,
-    heading: (i32, i32),
+pub struct State {
+    pub x: i32,
+    pub y: i32,
+    pub heading: (i32, i32),
 }
 
 impl State {
-    fn step(&mut self, i: &Instr) {
+    pub fn step(&mut self, i: &Instr) {
         match i {
             Instr::F(steps) => {
                 self.x += self.heading.0 * steps;
@@ -90,28 +120,24 @@ pub fn part1(input: &[Instr]) -> i32 {
         y: 0,
         heading: (1, 0),
     };
-    let mut debug_count = 0;
     for i in input {
         state.step(i);
-        if debug_count <= 10 {
-            println!("stepped by {:?}", i);
-            println!("state: {:?}", state);
-            debug_count += 1;
-        }
+        trace!("stepped by {:?}, state: {:?}", i, state);
     }
     state.x.abs() + state.y.abs()
 }
 
 /// We use standard coordinates with North = Up = positive y direction
+/// The waypoint is relative to the ship.
 #[derive(Debug)]
-struct State2 {
-    x: i32,
-    y: i32,
-    waypoint: (i32, i32),
+pub struct State2 {
+    pub x: i32,
+    pub y: i32,
+    pub waypoint: (i32, i32),
 }
 
 impl State2 {
-    fn step(&mut self, i: &Instr) {
+    pub fn step(&mut self, i: &Instr) {
         match i {
             Instr::F(steps) => {
                 self.x += self.waypoint.0 * steps;
@@ -146,14 +172,29 @@ pub fn part2(input: &[Instr]) -> i32 {
         y: 0,
         waypoint: (10, 1),
     };
-    let mut debug_count = 0;
     for i in input {
         state.step(i);
-        if debug_count <= 10 {
-            println!("stepped by {:?}", i);
-            println!("state: {:?}", state);
-            debug_count += 1;
-        }
+        trace!("stepped by {:?}, state: {:?}", i, state);
     }
     state.x.abs() + state.y.abs()
 }
+
+pub struct Day12;
+
+impl Solution for Day12 {
+    type Input = Vec<Instr>;
+    type Part1 = i32;
+    type Part2 = i32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        part2(input)
+    }
+}
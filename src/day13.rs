@@ -1,22 +1,76 @@
 use std::convert::TryInto;
 
+use crate::error::{ParseError, Source};
+use crate::example::Example;
+use crate::lint::Check;
+use crate::solution::Solution;
+
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: EXAMPLE,
+        expected: "295",
+    },
+    Example {
+        part: 2,
+        input: EXAMPLE,
+        expected: "1068781",
+    },
+    // Part 2 ignores the first line, and the description gives a few more schedules
+    // without one.
+    Example {
+        part: 2,
+        input: "0\n17,x,13,19",
+        expected: "3417",
+    },
+    Example {
+        part: 2,
+        input: "0\n1789,37,47,1889",
+        expected: "1202161486",
+    },
+];
+
+const EXAMPLE: &str = "939
+7,13,x,x,59,x,31,19";
+
 pub struct Input {
-    time: i32,
-    buses: Vec<i32>,
+    pub time: i32,
+    pub buses: Vec<i32>,
 }
 
 #[aoc_generator(day13, part1)]
-pub fn parse(input: &str) -> Input {
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    let src = Source::new(13, input);
     let mut lines = input.lines();
-    let time: i32 = lines.next().unwrap().parse().unwrap();
+    let time: i32 = src.number(lines.next().unwrap_or(input))?;
+    let buses = parse_buses(&src, lines.next())?
+        .into_iter()
+        .map(|(_, id)| id)
+        .collect();
+    Ok(Input { time, buses })
+}
+
+/// Parses the line of bus IDs into (position, ID) pairs, skipping the x's.
+fn parse_buses<T>(src: &Source, line: Option<&str>) -> Result<Vec<(usize, T)>, ParseError>
+where
+    T: std::str::FromStr + PartialOrd + From<u8>,
+{
+    let line = line.ok_or_else(|| src.eof("expected a line of bus IDs"))?;
     let mut buses = vec![];
-    for bus in lines.next().unwrap().split(',') {
+    for (i, bus) in line.split(',').enumerate() {
         if bus == "x" {
             continue;
         }
-        buses.push(bus.parse().unwrap());
+        let id: T = src.number(bus)?;
+        if id <= T::from(0) {
+            return Err(src.error(bus, "bus IDs must be positive"));
+        }
+        buses.push((i, id));
     }
-    Input { time, buses }
+    if buses.is_empty() {
+        return Err(src.error(line, "there are no buses"));
+    }
+    Ok(buses)
 }
 
 #[aoc(day13, part1)]
@@ -29,22 +83,20 @@ pub fn day1(input: &Input) -> i32 {
 }
 
 pub struct Input2 {
-    buses: Vec<(i64, i64)>,
+    /// (position in the list, bus ID) for each bus that isn't an x.
+    pub buses: Vec<(i64, i64)>,
 }
 
 #[aoc_generator(day13, part2)]
-pub fn parse2(input: &str) -> Input2 {
-    // let input = "\n17,x,13,19";
+pub fn parse2(input: &str) -> Result<Input2, ParseError> {
+    let src = Source::new(13, input);
     let mut lines = input.lines();
     lines.next();
-    let mut buses = vec![];
-    for (i, bus) in lines.next().unwrap().split(',').enumerate() {
-        if bus == "x" {
-            continue;
-        }
-        buses.push((i.try_into().unwrap(), bus.parse().unwrap()));
-    }
-    Input2 { buses }
+    let buses = parse_buses(&src, lines.next())?
+        .into_iter()
+        .map(|(i, id)| (i.try_into().unwrap(), id))
+        .collect();
+    Ok(Input2 { buses })
 }
 
 use modinverse::modinverse;
@@ -58,11 +110,11 @@ use modinverse::modinverse;
 #[aoc(day13, part2)]
 pub fn day2(input: &Input2) -> i64 {
     let product: i64 = input.buses.iter().map(|(_, id)| *id).product();
-    println!("product: {}", product);
+    debug!("product: {}", product);
     let mut answer = 0;
     for (val, id) in input.buses.iter() {
         let diff = (product / id) * modinverse(product / id, *id).unwrap();
-        println!("diff, diff mod id: {}, {}", diff, diff % id);
+        trace!("diff, diff mod id: {}, {}", diff, diff % id);
         answer += -val * diff;
         answer %= product;
     }
@@ -72,3 +124,68 @@ pub fn day2(input: &Input2) -> i64 {
         product + answer
     }
 }
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Strictly, `day2` only needs the IDs to be pairwise coprime for the inverses to exist, but
+/// primes are what it was written against, so that gets checked too.
+pub fn assumptions(input: &Input2) -> Vec<Check> {
+    let ids: Vec<i64> = input.buses.iter().map(|&(_, id)| id).collect();
+    let is_prime = |n: i64| n > 1 && (2..).take_while(|d| d * d <= n).all(|d| n % d != 0);
+    let not_prime = ids
+        .iter()
+        .filter(|&&id| !is_prime(id))
+        .map(|id| format!("{} isn't prime", id))
+        .collect();
+
+    let mut shared = vec![];
+    for (i, &a) in ids.iter().enumerate() {
+        for &b in &ids[i + 1..] {
+            let d = gcd(a, b);
+            if d != 1 {
+                shared.push(format!("{} and {} have a common factor of {}", a, b, d));
+            }
+        }
+    }
+
+    let overflow = match ids.iter().try_fold(1i64, |acc, &id| acc.checked_mul(id)) {
+        Some(_) => vec![],
+        None => vec!["the product of the IDs overflows an i64".to_owned()],
+    };
+
+    vec![
+        Check::new("day13::day2", "all bus IDs are prime", not_prime),
+        Check::new("day13::day2", "bus IDs are pairwise coprime", shared),
+        Check::new(
+            "day13::day2",
+            "the product of the bus IDs fits in an i64",
+            overflow,
+        ),
+    ]
+}
+
+pub struct Day13;
+
+impl Solution for Day13 {
+    type Input = (Input, Input2);
+    type Part1 = i32;
+    type Part2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        Ok((parse(input)?, parse2(input)?))
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        day1(&input.0)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        day2(&input.1)
+    }
+}
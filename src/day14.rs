@@ -3,6 +3,31 @@ use std::convert::TryInto;
 
 use regex::Regex;
 
+use crate::error::{ParseError, Source};
+use crate::example::Example;
+use crate::solution::Solution;
+
+/// The two parts need different examples: running part 2 on the part 1 example would write to
+/// 2^34 addresses.
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X
+mem[8] = 11
+mem[7] = 101
+mem[8] = 0",
+        expected: "165",
+    },
+    Example {
+        part: 2,
+        input: "mask = 000000000000000000000000000000X1001X
+mem[42] = 100
+mask = 00000000000000000000000000000000X0XX
+mem[26] = 1",
+        expected: "208",
+    },
+];
+
 #[derive(Debug)]
 pub enum Instr {
     Mask(String),
@@ -10,31 +35,30 @@ pub enum Instr {
 }
 
 #[aoc_generator(day14)]
-pub fn parse(input: &str) -> Vec<Instr> {
-    //     let input = "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X
-    // mem[8] = 11
-    // mem[7] = 101
-    // mem[8] = 0";
-
-    //     let input = "mask = 000000000000000000000000000000X1001X
-    // mem[42] = 100
-    // mask = 00000000000000000000000000000000X0XX
-    // mem[26] = 1";
-
-    let mask_regex = Regex::new("mask = ([X01]{36})").unwrap();
-    let assign_regex = Regex::new(r"mem\[(\d+)\] = (\d+)").unwrap();
+pub fn parse(input: &str) -> Result<Vec<Instr>, ParseError> {
+    let src = Source::new(14, input);
+    let mask_regex = Regex::new("^mask = ([X01]{36})$").unwrap();
+    let assign_regex = Regex::new(r"^mem\[(\d+)\] = (\d+)$").unwrap();
     let mut result = vec![];
     for line in input.lines() {
         if let Some(caps) = mask_regex.captures(line) {
-            result.push(Instr::Mask(caps[1].parse().unwrap()));
+            result.push(Instr::Mask(caps[1].to_owned()));
         } else if let Some(caps) = assign_regex.captures(line) {
             result.push(Instr::Assign(
-                caps[1].parse().unwrap(),
-                caps[2].parse().unwrap(),
+                src.number(caps.get(1).unwrap().as_str())?,
+                src.number(caps.get(2).unwrap().as_str())?,
+            ));
+        } else {
+            return Err(src.error(
+                line,
+                format!(
+                    "expected \"mask = <36 bits>\" or \"mem[<addr>] = <value>\", found {:?}",
+                    line
+                ),
             ));
         }
     }
-    result
+    Ok(result)
 }
 
 pub fn int_to_binary_string(i: &i64) -> String {
@@ -56,19 +80,13 @@ pub fn apply_mask(s: &i64, mask: &str) -> i64 {
 pub fn day1(input: &[Instr]) -> i64 {
     let mut mask = "";
     let mut mem: HashMap<i64, i64> = HashMap::new();
-    let mut count = 0;
     for instr in input {
-        if count <= 10 {
-            println!("{:?}", instr);
-            count += 1;
-        }
+        trace!("{:?}", instr);
         match instr {
             Instr::Mask(s) => mask = s,
             Instr::Assign(addr, val) => {
-                let applied = apply_mask(val, &mask);
-                if count <= 10 {
-                    println!("applied mask {} to {} and got {}", mask, val, applied);
-                }
+                let applied = apply_mask(val, mask);
+                trace!("applied mask {} to {} and got {}", mask, val, applied);
                 mem.insert(*addr, applied);
             }
         }
@@ -97,19 +115,13 @@ pub fn apply_mask_2(s: &i64, mask: &str) -> Vec<i64> {
 pub fn day2(input: &[Instr]) -> i64 {
     let mut mask = "";
     let mut mem: HashMap<i64, i64> = HashMap::new();
-    let mut count = 0;
     for instr in input {
-        if count <= 10 {
-            println!("{:?}", instr);
-            count += 1;
-        }
+        trace!("{:?}", instr);
         match instr {
             Instr::Mask(s) => mask = s,
             Instr::Assign(addr, val) => {
-                let addrs = apply_mask_2(addr, &mask);
-                if count <= 10 {
-                    println!("applied mask {} to {} and got {:?}", mask, val, addrs);
-                }
+                let addrs = apply_mask_2(addr, mask);
+                trace!("applied mask {} to {} and got {:?}", mask, addr, addrs);
                 for addr in addrs {
                     mem.insert(addr, *val);
                 }
@@ -118,3 +130,23 @@ pub fn day2(input: &[Instr]) -> i64 {
     }
     mem.values().sum()
 }
+
+pub struct Day14;
+
+impl Solution for Day14 {
+    type Input = Vec<Instr>;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        day1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        day2(input)
+    }
+}
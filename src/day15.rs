@@ -1,61 +1,311 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::Hash;
+use std::mem;
+
 use fnv::FnvHashMap;
-use std::collections::HashMap;
-use std::convert::TryInto;
 
+use crate::error::{ParseError, Source};
+use crate::example::Example;
+use crate::solution::Solution;
+
+// The description has part 2 answers for these too, but 30 million turns is too slow to run
+// as part of every debug-mode test run.
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: "0,3,6",
+        expected: "436",
+    },
+    Example {
+        part: 1,
+        input: "1,3,2",
+        expected: "1",
+    },
+    Example {
+        part: 1,
+        input: "3,1,2",
+        expected: "1836",
+    },
+];
+
+#[aoc_generator(day15)]
+pub fn parse(input: &str) -> Result<Vec<u32>, ParseError> {
+    let src = Source::new(15, input);
+    src.numbers(input, ',')
+}
+
+/// The type turns (and so the numbers spoken, which past the starting numbers are always
+/// less than the turn count) are counted in. `u32` halves the size of the last-seen table;
+/// `u64` is for going past `u32::MAX` turns.
+pub trait Turn: Copy + Eq + Hash + fmt::Debug + fmt::Display {
+    fn from_usize(n: usize) -> Self;
+    fn to_usize(self) -> usize;
+}
+
+impl Turn for u32 {
+    fn from_usize(n: usize) -> Self {
+        u32::try_from(n).expect("too many turns to count in a u32, use u64")
+    }
+
+    fn to_usize(self) -> usize {
+        self as usize
+    }
+}
+
+impl Turn for u64 {
+    fn from_usize(n: usize) -> Self {
+        n as u64
+    }
+
+    fn to_usize(self) -> usize {
+        self as usize
+    }
+}
+
+/// How the game remembers when each number was last spoken.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Engine {
+    /// Everything in a hash map. Nothing is allocated up front.
+    Hash,
+    /// A flat array indexed by number, preallocated up to a cutoff, with a hash map for
+    /// anything above it. Much faster, since nearly every number spoken is small.
+    #[default]
+    Array,
+}
+
+impl Engine {
+    pub fn parse(s: &str) -> Option<Engine> {
+        match s {
+            "hash" => Some(Engine::Hash),
+            "array" => Some(Engine::Array),
+            _ => None,
+        }
+    }
+}
+
+/// The most memory the array engine will preallocate. Anything that doesn't fit spills into
+/// the hash map. That's 64 million `u32` turns, comfortably more than part 2 needs.
+pub const ARRAY_BUDGET_BYTES: usize = 1 << 28;
+
+/// Last-seen turns for numbers below the cutoff live in `array` (0 meaning never seen, since
+/// turns start at 1); the rest live in `overflow`.
+#[derive(Debug, Clone)]
+struct LastSeen<T> {
+    array: Vec<T>,
+    overflow: FnvHashMap<T, T>,
+}
+
+impl<T: Turn> LastSeen<T> {
+    fn new(cutoff: usize) -> Self {
+        LastSeen {
+            array: vec![T::from_usize(0); cutoff],
+            overflow: FnvHashMap::default(),
+        }
+    }
+
+    fn get(&self, number: T) -> Option<T> {
+        match self.array.get(number.to_usize()) {
+            Some(&turn) if turn == T::from_usize(0) => None,
+            Some(&turn) => Some(turn),
+            None => self.overflow.get(&number).copied(),
+        }
+    }
+
+    fn insert(&mut self, number: T, turn: T) {
+        match self.array.get_mut(number.to_usize()) {
+            Some(slot) => *slot = turn,
+            None => {
+                self.overflow.insert(number, turn);
+            }
+        }
+    }
+}
+
+/// The memory game, as an iterator over the numbers spoken: first the starting numbers, then
+/// each number after that is how many turns apart the previous number was last spoken,
+/// or 0 if it hadn't been spoken before.
+///
 /// We track state as:
-/// - The current turn (i.e. how many numbers have been said already, plus 1)
+/// - The current turn (i.e. how many numbers have been said already)
 /// - The number said on the previous turn
 /// - Map of [number]=>[most recent turn] but NOT yet updated for the previous turn
+///
 /// Then, we use those to compute the next number, and update the map with the previous turn.
-#[aoc(day15, part1)]
-pub fn part1(_: &str) -> i64 {
-    let mut base = vec![1, 17, 0, 10, 18, 11, 6];
-    // let mut base = vec![0, 3, 6];
-    let mut prev = base.pop().unwrap();
-    let mut map: HashMap<i64, usize> = base.into_iter().zip(1..).collect();
-    let mut turn = map.len() + 2;
-    while turn <= 2020 {
-        match map.get(&prev) {
-            Some(v) => {
-                let n = (turn - v - 1).try_into().unwrap();
-                map.insert(prev, turn - 1);
-                prev = n;
-            }
-            None => {
-                map.insert(prev, turn - 1);
-                prev = 0;
+#[derive(Debug, Clone)]
+pub struct MemoryGame<T = u32> {
+    seed: Vec<T>,
+    turn: T,
+    prev: T,
+    last_seen: LastSeen<T>,
+}
+
+impl<T: Turn> MemoryGame<T> {
+    /// A game that keeps everything in a hash map.
+    pub fn new(seed: &[u32]) -> Self {
+        MemoryGame::with_cutoff(seed, 0)
+    }
+
+    /// A game whose last-seen table is an array for the numbers below `cutoff`.
+    pub fn with_cutoff(seed: &[u32], cutoff: usize) -> Self {
+        MemoryGame {
+            seed: seed.iter().map(|&n| T::from_usize(n as usize)).collect(),
+            turn: T::from_usize(0),
+            prev: T::from_usize(0),
+            last_seen: LastSeen::new(cutoff),
+        }
+    }
+
+    /// A game set up to play up to `turns` turns with the given engine. Every number spoken
+    /// after the starting ones is less than the number of turns, so the array engine never
+    /// needs more entries than that (or than the memory budget allows). A starting number
+    /// that's bigger goes in the overflow map, like anything else past the cutoff.
+    pub fn with_engine(seed: &[u32], engine: Engine, turns: usize) -> Self {
+        match engine {
+            Engine::Hash => MemoryGame::new(seed),
+            Engine::Array => {
+                let cutoff = turns.min(ARRAY_BUDGET_BYTES / mem::size_of::<T>());
+                MemoryGame::with_cutoff(seed, cutoff)
             }
         }
-        println!("on turn {}, prev: {}", turn, prev);
-        turn += 1;
     }
-    prev
+}
+
+impl<T: Turn> Iterator for MemoryGame<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let turn = self.turn.to_usize();
+        let next = match self.seed.get(turn) {
+            Some(&n) => n,
+            None => match self.last_seen.get(self.prev) {
+                Some(seen) => T::from_usize(turn - seen.to_usize()),
+                None => T::from_usize(0),
+            },
+        };
+        if turn > 0 {
+            self.last_seen.insert(self.prev, self.turn);
+        }
+        self.turn = T::from_usize(turn + 1);
+        self.prev = next;
+        trace!("on turn {}, spoke {}", self.turn, next);
+        Some(next)
+    }
+}
+
+/// The number spoken on the given (1-based) turn, using the array engine.
+pub fn spoken_on<T: Turn>(seed: &[u32], turn: T) -> T {
+    spoken_on_with(seed, turn, Engine::Array)
+}
+
+pub fn spoken_on_with<T: Turn>(seed: &[u32], turn: T, engine: Engine) -> T {
+    let turn = turn.to_usize();
+    assert!(turn > 0, "turns start at 1");
+    MemoryGame::with_engine(seed, engine, turn)
+        .nth(turn - 1)
+        .unwrap()
+}
+
+#[aoc(day15, part1)]
+pub fn part1(seed: &[u32]) -> u32 {
+    part1_with(seed, Engine::Array)
+}
+
+pub fn part1_with(seed: &[u32], engine: Engine) -> u32 {
+    spoken_on_with(seed, 2020u32, engine)
 }
 
 /// Err... is there something clver I'm supposed to do here?
-/// This is just essentially copy-paste part 1, but with the turn count ramped up.
+/// This is just essentially part 1, but with the turn count ramped up.
 /// This ran in under 2 seconds which would be the slowest runtime so far this AoC.
 /// But it's not outrageous, either...
 /// Post-hoc: I've tried to optimize this a bit more but it's still just a touch over 1 second.
+/// Post-post-hoc: swapping the hash map for a flat array indexed by number more than halves it.
 #[aoc(day15, part2)]
-pub fn part2(_: &str) -> i32 {
-    let mut base = vec![1, 17, 0, 10, 18, 11, 6];
-    let mut prev = base.pop().unwrap();
-    let mut map: FnvHashMap<i32, i32> = base.into_iter().zip(1..).collect();
-    let mut turn: i32 = (map.len() + 2).try_into().unwrap();
-    while turn <= 30_000_000 {
-        match map.entry(prev) {
-            std::collections::hash_map::Entry::Occupied(mut o) => {
-                let n = turn - o.get() - 1;
-                o.insert(turn - 1);
-                prev = n;
-            }
-            std::collections::hash_map::Entry::Vacant(v) => {
-                v.insert(turn - 1);
-                prev = 0;
-            }
+pub fn part2(seed: &[u32]) -> u32 {
+    part2_with(seed, Engine::Array)
+}
+
+pub fn part2_with(seed: &[u32], engine: Engine) -> u32 {
+    spoken_on_with(seed, 30_000_000u32, engine)
+}
+
+pub struct Day15;
+
+impl Solution for Day15 {
+    type Input = Vec<u32>;
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        part2(input)
+    }
+}
+
+/// Day 15 on the hash map engine, so it can be timed against the array. It's registered as
+/// `solution::DAY15_HASH` rather than in `DAYS`.
+pub struct Day15Hash;
+
+impl Solution for Day15Hash {
+    type Input = Vec<u32>;
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        part1_with(input, Engine::Hash)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        part2_with(input, Engine::Hash)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sequence() {
+        let spoken: Vec<u32> = MemoryGame::new(&[0, 3, 6]).take(10).collect();
+        assert_eq!(spoken, vec![0, 3, 6, 0, 3, 3, 1, 0, 4, 0]);
+        assert_eq!(spoken_on(&[0, 3, 6], 4u32), 0);
+        assert_eq!(spoken_on(&[2, 1, 3], 2020u32), 10);
+    }
+
+    #[test]
+    fn test_engines_agree() {
+        let seed = [1, 17, 0, 10, 18, 11, 6];
+        let hash: Vec<u32> = MemoryGame::new(&seed).take(5000).collect();
+        // A tiny cutoff, so plenty of numbers go through the overflow map too
+        let mixed: Vec<u32> = MemoryGame::with_cutoff(&seed, 8).take(5000).collect();
+        let array: Vec<u64> = MemoryGame::with_engine(&seed, Engine::Array, 5000)
+            .take(5000)
+            .collect();
+        assert_eq!(hash, mixed);
+        assert!(hash.iter().zip(&array).all(|(&a, &b)| a as u64 == b));
+        assert_eq!(
+            spoken_on_with(&[3, 1, 2], 2020u64, Engine::Hash),
+            spoken_on_with(&[3, 1, 2], 2020u64, Engine::Array)
+        );
+        // Starting numbers bigger than the cutoff, which only covers the turns
+        for seed in [[100, 3, 0], [0, 3, 4_000_000]].iter() {
+            assert_eq!(
+                spoken_on_with(seed, 10u32, Engine::Hash),
+                spoken_on_with(seed, 10u32, Engine::Array)
+            );
         }
-        turn += 1;
+        assert_eq!(spoken_on_with(&[100, 3, 100], 4u32, Engine::Array), 2);
     }
-    prev
 }
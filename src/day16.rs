@@ -3,60 +3,107 @@ use std::collections::HashSet;
 
 use regex::Regex;
 
+use crate::error::{ParseError, Source};
+use crate::example::Example;
+use crate::lint::{self, Check};
+use crate::solution::Solution;
+
+/// The part 2 example in the description doesn't have any "departure" fields, so there's no
+/// answer to check it against.
+pub const EXAMPLES: &[Example] = &[Example {
+    part: 1,
+    input: "class: 1-3 or 5-7
+row: 6-11 or 33-44
+seat: 13-40 or 45-50
+
+your ticket:
+7,1,14
+
+nearby tickets:
+7,3,47
+40,4,50
+55,2,20
+38,6,12",
+    expected: "71",
+}];
+
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub struct Rule {
-    field: String,
-    bounds: Vec<(i64, i64)>,
+    pub field: String,
+    /// Inclusive ranges of values that are valid for the field.
+    pub bounds: Vec<(i64, i64)>,
 }
 
-impl From<&str> for Rule {
-    fn from(s: &str) -> Self {
-        let re = Regex::new(r"(.*): (\d+)-(\d+) or (\d+)-(\d+)").unwrap();
-        let caps = re.captures(s).unwrap();
+impl Rule {
+    fn parse(src: &Source, re: &Regex, s: &str) -> Result<Self, ParseError> {
+        let caps = re.captures(s).ok_or_else(|| {
+            src.error(
+                s,
+                format!("expected a rule like \"class: 1-3 or 5-7\", found {:?}", s),
+            )
+        })?;
         let field = caps.get(1).unwrap().as_str().to_owned();
-        let bounds = vec![
-            (
-                caps.get(2).unwrap().as_str().parse().unwrap(),
-                caps.get(3).unwrap().as_str().parse().unwrap(),
-            ),
-            (
-                caps.get(4).unwrap().as_str().parse().unwrap(),
-                caps.get(5).unwrap().as_str().parse().unwrap(),
-            ),
-        ];
-        Rule { field, bounds }
+        let num = |i| src.number(caps.get(i).unwrap().as_str());
+        let bounds = vec![(num(2)?, num(3)?), (num(4)?, num(5)?)];
+        Ok(Rule { field, bounds })
+    }
+
+    /// Whether `value` is valid for this field.
+    pub fn allows(&self, value: i64) -> bool {
+        in_bounds(value, &self.bounds)
     }
 }
 
-type Ticket = Vec<i64>;
+pub type Ticket = Vec<i64>;
 
 #[aoc_generator(day16)]
-pub fn parse(input: &str) -> (Vec<Rule>, Ticket, Vec<Ticket>) {
+pub fn parse(input: &str) -> Result<(Vec<Rule>, Ticket, Vec<Ticket>), ParseError> {
+    let src = Source::new(16, input);
     let mut groups = input.split("\n\n");
-    let rules: Vec<Rule> = groups.next().unwrap().lines().map(|x| x.into()).collect();
+    let re = Regex::new(r"^(.*): (\d+)-(\d+) or (\d+)-(\d+)$").unwrap();
+    let rules: Vec<Rule> = groups
+        .next()
+        .unwrap()
+        .lines()
+        .map(|x| Rule::parse(&src, &re, x))
+        .collect::<Result<_, _>>()?;
     for rule in rules.iter() {
-        println!("{:?}", rule);
+        debug!("{:?}", rule);
     }
 
-    let my_ticket_lines = groups.next().unwrap();
+    let my_ticket_lines = groups
+        .next()
+        .ok_or_else(|| src.eof("expected a \"your ticket:\" section"))?;
 
-    let my_ticket = my_ticket_lines
-        .lines()
-        .nth(1)
-        .unwrap()
-        .split(',')
-        .map(|x| x.parse().unwrap())
-        .collect();
-    println!("{:?}", my_ticket);
+    let my_ticket_line = my_ticket_lines.lines().nth(1).ok_or_else(|| {
+        src.error(
+            my_ticket_lines,
+            "expected your ticket after \"your ticket:\"",
+        )
+    })?;
+    let my_ticket: Ticket = src.numbers(my_ticket_line, ',')?;
+    debug!("my ticket: {:?}", my_ticket);
 
-    let neary_ticket_lines = groups.next().unwrap();
-    let nearby_tickets = neary_ticket_lines
-        .lines()
-        .skip(1)
-        .map(|s| s.split(',').map(|x| x.parse().unwrap()).collect())
-        .collect();
+    let neary_ticket_lines = groups
+        .next()
+        .ok_or_else(|| src.eof("expected a \"nearby tickets:\" section"))?;
+    let mut nearby_tickets = vec![];
+    for line in neary_ticket_lines.lines().skip(1) {
+        let ticket: Ticket = src.numbers(line, ',')?;
+        if ticket.len() != my_ticket.len() {
+            return Err(src.error(
+                line,
+                format!(
+                    "ticket has {} fields, but yours has {}",
+                    ticket.len(),
+                    my_ticket.len()
+                ),
+            ));
+        }
+        nearby_tickets.push(ticket);
+    }
 
-    (rules, my_ticket, nearby_tickets)
+    Ok((rules, my_ticket, nearby_tickets))
 }
 
 /// This one's a bit more complex than the problems so far
@@ -70,7 +117,7 @@ pub fn day1(input: &(Vec<Rule>, Ticket, Vec<Ticket>)) -> i64 {
     for rule in input.0.iter() {
         merged_bounds = merge(&merged_bounds, &rule.bounds);
     }
-    println!("{:?}", merged_bounds);
+    debug!("merged bounds: {:?}", merged_bounds);
     let mut ans = 0;
     for ticket in &input.2 {
         for val in ticket.iter() {
@@ -134,8 +181,9 @@ fn in_bounds(key: i64, bounds: &[(i64, i64)]) -> bool {
     false
 }
 
-#[aoc(day16, part2)]
-pub fn day2(input: &(Vec<Rule>, Ticket, Vec<Ticket>)) -> i64 {
+/// For each rule, the positions on the ticket it could be, going by the nearby tickets that
+/// are valid.
+pub fn field_candidates(input: &(Vec<Rule>, Ticket, Vec<Ticket>)) -> HashMap<Rule, HashSet<usize>> {
     // First we need to mimic part 1 to filter out the invalid tickets
     let mut merged_bounds = vec![];
     for rule in input.0.iter() {
@@ -162,14 +210,20 @@ pub fn day2(input: &(Vec<Rule>, Ticket, Vec<Ticket>)) -> i64 {
             let fields_copy = potential_fields.iter().copied().collect::<Vec<_>>();
             for field in fields_copy {
                 let value = ticket[field];
-                if !in_bounds(value, &rule.bounds) {
+                if !rule.allows(value) {
                     potential_fields.remove(&field);
                 }
             }
         }
-        println!("{}: {:?}", rule.field, potential_fields);
+        debug!("{}: {:?}", rule.field, potential_fields);
         possibilities.insert(rule.clone(), potential_fields);
     }
+    possibilities
+}
+
+#[aoc(day16, part2)]
+pub fn day2(input: &(Vec<Rule>, Ticket, Vec<Ticket>)) -> i64 {
+    let mut possibilities = field_candidates(input);
 
     // by inspection (i.e those print statement), it wasn't enough to just make that one pass
     // we need another pass to do a logic/constraint solving
@@ -184,7 +238,7 @@ pub fn day2(input: &(Vec<Rule>, Ticket, Vec<Ticket>)) -> i64 {
         let rule = only_one.0.clone();
         let values: Vec<_> = only_one.1.iter().collect();
         let value = *values[0];
-        println!("{} is field {}", rule.field, value);
+        info!("{} is field {}", rule.field, value);
         possibilities.remove(&rule);
         final_mapping.insert(rule, value);
         for (_, v) in possibilities.iter_mut() {
@@ -201,3 +255,73 @@ pub fn day2(input: &(Vec<Rule>, Ticket, Vec<Ticket>)) -> i64 {
     }
     prod
 }
+
+pub fn assumptions(input: &(Vec<Rule>, Ticket, Vec<Ticket>)) -> Vec<Check> {
+    let candidates = field_candidates(input)
+        .into_iter()
+        .map(|(rule, fields)| (rule.field, fields))
+        .collect();
+    let leftover = lint::eliminate::<String, usize>(candidates);
+    vec![Check::new(
+        "day16::day2",
+        "there's always a field with exactly one candidate position left",
+        lint::unresolved(&leftover),
+    )]
+}
+
+pub struct Day16;
+
+impl Solution for Day16 {
+    type Input = (Vec<Rule>, Ticket, Vec<Ticket>);
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        day1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        day2(input)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_assumptions() {
+        let input = parse(
+            "class: 0-1 or 4-19
+row: 0-5 or 8-19
+seat: 0-13 or 16-19
+
+your ticket:
+11,12,13
+
+nearby tickets:
+3,9,18
+15,1,5
+5,14,9",
+        )
+        .unwrap();
+        assert!(assumptions(&input).iter().all(Check::passed));
+
+        // Nothing tells the two fields apart
+        let input =
+            parse("a: 0-5 or 8-19\nb: 0-5 or 8-19\n\nyour ticket:\n1,2\n\nnearby tickets:\n3,4")
+                .unwrap();
+        let failures: Vec<_> = assumptions(&input)
+            .into_iter()
+            .filter_map(|c| c.failure)
+            .collect();
+        assert_eq!(
+            failures,
+            vec!["a could be any of 0, 1; b could be any of 0, 1"]
+        );
+    }
+}
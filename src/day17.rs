@@ -7,13 +7,34 @@
 use std::collections::HashMap;
 use std::convert::TryInto;
 
+use crate::error::{ParseError, Source};
+use crate::example::Example;
+use crate::solution::Solution;
+
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: EXAMPLE,
+        expected: "112",
+    },
+    Example {
+        part: 2,
+        input: EXAMPLE,
+        expected: "848",
+    },
+];
+
+const EXAMPLE: &str = ".#.
+..#
+###";
+
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
 pub enum Square {
     Inactive,
     Active,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Default)]
 pub struct Grid {
     grid: HashMap<(i32, i32, i32), Square>,
     min_x: i32,
@@ -44,27 +65,27 @@ impl std::fmt::Display for Grid {
 }
 
 impl Grid {
-    fn new() -> Self {
-        Grid {
-            grid: HashMap::new(),
-            min_x: 0,
-            max_x: 0,
-            min_y: 0,
-            max_y: 0,
-            min_z: 0,
-            max_z: 0,
-        }
+    /// An empty grid, where every cube is inactive.
+    pub fn new() -> Self {
+        Grid::default()
     }
 
-    fn get(&self, x: i32, y: i32, z: i32) -> Square {
+    pub fn get(&self, x: i32, y: i32, z: i32) -> Square {
         self.grid
             .get(&(x, y, z))
             .cloned()
             .unwrap_or(Square::Inactive)
     }
 
-    fn set(&mut self, x: i32, y: i32, z: i32, sq: Square) {
+    /// Sets one cube, growing the bounds of the grid to include it if needed.
+    pub fn set(&mut self, x: i32, y: i32, z: i32, sq: Square) {
         self.grid.insert((x, y, z), sq);
+        self.min_x = self.min_x.min(x);
+        self.max_x = self.max_x.max(x);
+        self.min_y = self.min_y.min(y);
+        self.max_y = self.max_y.max(y);
+        self.min_z = self.min_z.min(z);
+        self.max_z = self.max_z.max(z);
     }
 
     fn active_neighbors(&self, x: i32, y: i32, z: i32) -> usize {
@@ -91,7 +112,13 @@ impl Grid {
         count
     }
 
-    fn next_grid(&self) -> Grid {
+    /// How many cubes are active.
+    pub fn active(&self) -> usize {
+        self.grid.values().filter(|x| **x == Square::Active).count()
+    }
+
+    /// Runs one cycle of the simulation.
+    pub fn next_grid(&self) -> Grid {
         let mut next = Grid::new();
         for x in self.min_x - 1..self.max_x + 2 {
             for y in self.min_y - 1..self.max_y + 2 {
@@ -118,10 +145,29 @@ impl Grid {
     }
 }
 
-pub fn parse(input: &str) -> Grid {
+/// Checks that the input is a non-empty rectangle of '#' and '.', so that the parsers below
+/// don't have to.
+fn check_slice(src: &Source) -> Result<(), ParseError> {
+    let width = src.input.lines().next().map(str::len).unwrap_or(0);
+    if width == 0 {
+        return Err(src.eof("the initial slice is empty"));
+    }
+    for line in src.input.lines() {
+        if let Some(i) = line.find(|c| c != '#' && c != '.') {
+            return Err(src.error(&line[i..], "expected '#' or '.'"));
+        }
+        if line.len() != width {
+            return Err(src.error(line, "all rows of the initial slice must be the same width"));
+        }
+    }
+    Ok(())
+}
+
+#[aoc_generator(day17, part1)]
+pub fn parse(input: &str) -> Result<Grid, ParseError> {
+    check_slice(&Source::new(17, input))?;
     let mut grid = Grid::new();
     for (y, line) in input.lines().enumerate() {
-        grid.max_x = (line.len() - 1).try_into().unwrap();
         for (x, c) in line.chars().enumerate() {
             match c {
                 '#' => grid.set(
@@ -136,34 +182,29 @@ pub fn parse(input: &str) -> Grid {
                     0,
                     Square::Inactive,
                 ),
-                x => panic!("bad input char {}", x),
+                x => unreachable!("bad input char {}", x),
             }
         }
     }
-    grid.max_y = (input.lines().count() - 1).try_into().unwrap();
-    grid
+    Ok(grid)
 }
 
 #[aoc(day17, part1)]
-pub fn day17(input: &str) -> usize {
-    // example
-    //     let input = r".#.
-    // ..#
-    // ###";
-    let mut grid = parse(input);
-    println!("{}", grid);
+pub fn day17(input: &Grid) -> usize {
+    let mut grid = input.clone();
+    trace!("initial grid:\n{}", grid);
 
     for _ in 0..6 {
         grid = grid.next_grid();
     }
-    grid.grid.values().filter(|x| **x == Square::Active).count()
+    grid.active()
 }
 
 // Ok, and again, with the power of copy paste!
 // Trying to make this all parametric over the number of dimensions is just more effor than I
 // I feel like putting into this annoying problem.
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Default)]
 pub struct Grid4D {
     grid: HashMap<(i32, i32, i32, i32), Square>,
     min_x: i32,
@@ -199,29 +240,29 @@ impl std::fmt::Display for Grid4D {
 }
 
 impl Grid4D {
-    fn new() -> Self {
-        Grid4D {
-            grid: HashMap::new(),
-            min_x: 0,
-            max_x: 0,
-            min_y: 0,
-            max_y: 0,
-            min_z: 0,
-            max_z: 0,
-            min_w: 0,
-            max_w: 0,
-        }
+    /// An empty grid, where every cube is inactive.
+    pub fn new() -> Self {
+        Grid4D::default()
     }
 
-    fn get(&self, x: i32, y: i32, z: i32, w: i32) -> Square {
+    pub fn get(&self, x: i32, y: i32, z: i32, w: i32) -> Square {
         self.grid
             .get(&(x, y, z, w))
             .cloned()
             .unwrap_or(Square::Inactive)
     }
 
-    fn set(&mut self, x: i32, y: i32, z: i32, w: i32, sq: Square) {
+    /// Sets one cube, growing the bounds of the grid to include it if needed.
+    pub fn set(&mut self, x: i32, y: i32, z: i32, w: i32, sq: Square) {
         self.grid.insert((x, y, z, w), sq);
+        self.min_x = self.min_x.min(x);
+        self.max_x = self.max_x.max(x);
+        self.min_y = self.min_y.min(y);
+        self.max_y = self.max_y.max(y);
+        self.min_z = self.min_z.min(z);
+        self.max_z = self.max_z.max(z);
+        self.min_w = self.min_w.min(w);
+        self.max_w = self.max_w.max(w);
     }
 
     fn active_neighbors(&self, x: i32, y: i32, z: i32, w: i32) -> usize {
@@ -250,7 +291,13 @@ impl Grid4D {
         count
     }
 
-    fn next_grid(&self) -> Grid4D {
+    /// How many cubes are active.
+    pub fn active(&self) -> usize {
+        self.grid.values().filter(|x| **x == Square::Active).count()
+    }
+
+    /// Runs one cycle of the simulation.
+    pub fn next_grid(&self) -> Grid4D {
         let mut next = Grid4D::new();
         for x in self.min_x - 1..self.max_x + 2 {
             for y in self.min_y - 1..self.max_y + 2 {
@@ -281,46 +328,50 @@ impl Grid4D {
     }
 }
 
-pub fn parse4d(input: &str) -> Grid4D {
-    let mut grid = Grid4D::new();
-    for (y, line) in input.lines().enumerate() {
-        grid.max_x = (line.len() - 1).try_into().unwrap();
-        for (x, c) in line.chars().enumerate() {
-            match c {
-                '#' => grid.set(
-                    x.try_into().unwrap(),
-                    y.try_into().unwrap(),
-                    0,
-                    0,
-                    Square::Active,
-                ),
-                '.' => grid.set(
-                    x.try_into().unwrap(),
-                    y.try_into().unwrap(),
-                    0,
-                    0,
-                    Square::Inactive,
-                ),
-                x => panic!("bad input char {}", x),
-            }
+/// The 3D grid as the w=0 slice of a 4D one.
+impl From<&Grid> for Grid4D {
+    fn from(grid: &Grid) -> Self {
+        let mut grid4d = Grid4D::new();
+        for (&(x, y, z), &sq) in &grid.grid {
+            grid4d.set(x, y, z, 0, sq);
         }
+        grid4d
     }
-    grid.max_y = (input.lines().count() - 1).try_into().unwrap();
-    grid
+}
+
+#[aoc_generator(day17, part2)]
+pub fn parse4d(input: &str) -> Result<Grid4D, ParseError> {
+    Ok(Grid4D::from(&parse(input)?))
 }
 
 #[aoc(day17, part2)]
-pub fn day17_2(input: &str) -> usize {
-    // example:
-    // let input = ".#.\n..#\n###";
-    let mut grid = parse4d(input);
-    println!("{}", grid);
+pub fn day17_2(input: &Grid4D) -> usize {
+    let mut grid = input.clone();
+    trace!("initial grid:\n{}", grid);
 
     for i in 0..6 {
         grid = grid.next_grid();
-        if i == 0 {
-            println!("{}", grid);
-        }
+        trace!("after cycle {}:\n{}", i + 1, grid);
+    }
+    grid.active()
+}
+
+pub struct Day17;
+
+impl Solution for Day17 {
+    type Input = Grid;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        day17(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        day17_2(&Grid4D::from(input))
     }
-    grid.grid.values().filter(|x| **x == Square::Active).count()
 }
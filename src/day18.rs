@@ -6,6 +6,29 @@
 //! I could have used any of the lovely parsing libs for
 //! Rust (e.g. lalrpop or pest) but it was more fun to do it myself.
 
+use crate::error::{ParseError, Source};
+use crate::example::Example;
+use crate::solution::Solution;
+
+/// The examples from the description, one per line, so the answer is their sum.
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: EXAMPLE,
+        expected: "26335",
+    },
+    Example {
+        part: 2,
+        input: EXAMPLE,
+        expected: "693891",
+    },
+];
+
+const EXAMPLE: &str = "2 * 3 + (4 * 5)
+5 + (8 * 3 + 9 + 3 * 4 * 3)
+5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))
+((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2";
+
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
 enum Token {
     LParen,
@@ -16,16 +39,16 @@ enum Token {
 }
 
 fn tokenize(mut input: &str) -> Vec<Token> {
-    let mut input = &mut input;
+    let input = &mut input;
     let mut result = vec![];
-    while let Some(t) = next_token(&mut input) {
+    while let Some(t) = next_token(input) {
         result.push(t)
     }
     result
 }
 
 fn next_token(input: &mut &str) -> Option<Token> {
-    *input = &mut input.trim_start();
+    *input = input.trim_start();
     if input.is_empty() {
         return None;
     }
@@ -48,8 +71,8 @@ fn next_token(input: &mut &str) -> Option<Token> {
         }
         '0'..='9' => {
             let last_idx = input
-                .find(|c| c < '0' || c > '9')
-                .unwrap_or_else(|| input.len());
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(input.len());
             let num_str = &input[..last_idx];
             let ret = Token::Value(num_str.parse().unwrap());
             *input = &input[last_idx..];
@@ -59,12 +82,16 @@ fn next_token(input: &mut &str) -> Option<Token> {
     }
 }
 
-fn evaluate_1(input: &str) -> i64 {
+/// Evaluates an expression with the part 1 rules, where + and * have the same precedence.
+/// The expression has to be well-formed (see `parse`); this panics if it isn't.
+pub fn evaluate_1(input: &str) -> i64 {
     let tokens = tokenize(input);
     parse_expr_1(&mut tokens.as_ref())
 }
 
-fn evaluate_2(input: &str) -> i64 {
+/// Evaluates an expression with the part 2 rules, where + binds tighter than *.
+/// The expression has to be well-formed (see `parse`); this panics if it isn't.
+pub fn evaluate_2(input: &str) -> i64 {
     let tokens = tokenize(input);
     parse_expr_2(tokens.as_ref())
 }
@@ -117,6 +144,7 @@ fn parse_expr_1(input: &mut &[Token]) -> i64 {
 /// - Stack is topped by Value + Value, next token is anything
 /// - Stack is topped by Right Paren, next token is anything
 /// - Stack is topped by Value * Value, next token is empty or *
+///
 /// Strictly speaking, doing it this way (consuming left to right) is determining the associativity
 /// (it's making a + b + c work out as (a+b) + c )
 /// But since we're working with normal + and *, they are actually associative, so it doesn't matter how we do it.
@@ -127,6 +155,7 @@ fn parse_expr_1(input: &mut &[Token]) -> i64 {
 /// - In some ways the truly hard part of parsing is error handling - how can you give the user
 ///   a meaningful error message, guess what they were trying to do, or ignore a piece of the input and
 ///   continue onward, so that you can e.g. catch multiple errors in a single compile.
+///
 /// We don't have to do any of that :)
 fn parse_expr_2(input: &[Token]) -> i64 {
     let mut stack = vec![];
@@ -137,9 +166,9 @@ fn parse_expr_2(input: &[Token]) -> i64 {
         } else {
             None
         };
-        println!("{:?}, {:?}", stack, peek);
+        trace!("{:?}, {:?}", stack, peek);
         while reduce_once(&mut stack, peek) {
-            println!("{:?}, {:?}", stack, peek);
+            trace!("{:?}, {:?}", stack, peek);
         }
     }
     let t = stack.pop().unwrap();
@@ -159,37 +188,37 @@ fn reduce_once(stack: &mut Vec<Token>, peek: Option<Token>) -> bool {
                 stack.pop();
                 stack.pop();
                 stack.push(Token::Value(lhs + rhs));
-                println!("reduced by plus, pushing {}", lhs + rhs);
+                trace!("reduced by plus, pushing {}", lhs + rhs);
                 return true;
             } else {
                 panic!()
             }
         } else if l >= 2
             && stack[l - 1] == Token::Times
-            && (peek == None || peek == Some(Token::Times) || peek == Some(Token::RParen))
+            && (peek.is_none() || peek == Some(Token::Times) || peek == Some(Token::RParen))
         {
             if let Token::Value(lhs) = stack[l - 2] {
                 stack.pop();
                 stack.pop();
                 stack.push(Token::Value(lhs * rhs));
-                println!("reduced by times, pushing {}", lhs * rhs);
+                trace!("reduced by times, pushing {}", lhs * rhs);
                 return true;
             } else {
                 panic!()
             }
         } else {
-            println!("no reduction, pushing {:?}", token);
+            trace!("no reduction, pushing {:?}", token);
             stack.push(token);
         }
     } else if let Token::RParen = token {
         // on a well formed input, the stack should be "(" then Value
         let value = stack.pop().unwrap();
         stack.pop();
-        println!("Got RParen, flattening to {:?}", value);
+        trace!("Got RParen, flattening to {:?}", value);
         stack.push(value);
         return true;
     } else {
-        println!("no reduction, pushing {:?}", token);
+        trace!("no reduction, pushing {:?}", token);
         stack.push(token);
     }
     false
@@ -201,7 +230,7 @@ mod test {
     #[test]
     fn test_next() {
         let mut s = "1 + (2 * 3) + (4 * (5 + 6))";
-        let t = s.clone();
+        let t = s;
         assert_eq!(next_token(&mut s), Some(Token::Value(1)));
 
         assert_eq!(next_token(&mut s), Some(Token::Plus));
@@ -253,18 +282,18 @@ mod test {
 
     #[test]
     fn test_parse() {
-        assert_eq!(evaluate_1(&mut "1 + 2 * 3 + 4 * 5 + 6"), 71);
-        assert_eq!(evaluate_1(&mut "1 + (2 * 3) + (4 * (5 + 6))"), 51);
-        assert_eq!(evaluate_1(&mut "(1 + 2) + 3"), 6);
-        assert_eq!(evaluate_1(&mut "2 * 3 + (4 * 5)"), 26);
+        assert_eq!(evaluate_1("1 + 2 * 3 + 4 * 5 + 6"), 71);
+        assert_eq!(evaluate_1("1 + (2 * 3) + (4 * (5 + 6))"), 51);
+        assert_eq!(evaluate_1("(1 + 2) + 3"), 6);
+        assert_eq!(evaluate_1("2 * 3 + (4 * 5)"), 26);
 
-        assert_eq!(evaluate_1(&mut "5 + (8 * 3 + 9 + 3 * 4 * 3)"), 437);
+        assert_eq!(evaluate_1("5 + (8 * 3 + 9 + 3 * 4 * 3)"), 437);
         assert_eq!(
-            evaluate_1(&mut "5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))"),
+            evaluate_1("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))"),
             12240
         );
         assert_eq!(
-            evaluate_1(&mut "((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2"),
+            evaluate_1("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2"),
             13632
         );
     }
@@ -285,14 +314,101 @@ mod test {
             23340
         );
     }
+
+    #[test]
+    fn test_check_line() {
+        let input = "1 + (2 * 3)\n1 + * 2\n(1 + 2\n4 + 5)\n1 +\n7 - 3";
+        let src = Source::new(18, input);
+        let lines: Vec<_> = input.lines().collect();
+        assert_eq!(check_line(&src, lines[0]), Ok(()));
+        assert_eq!(check_line(&src, lines[1]).unwrap_err().column, 5);
+        assert_eq!(
+            check_line(&src, lines[2]).unwrap_err().message,
+            "missing ')'"
+        );
+        assert_eq!(
+            check_line(&src, lines[3]).unwrap_err().message,
+            "unmatched ')'"
+        );
+        assert_eq!(check_line(&src, lines[4]).unwrap_err().column, 4);
+        assert_eq!(check_line(&src, lines[5]).unwrap_err().column, 3);
+    }
+}
+
+/// The parsers above assume they're given a well-formed expression, so we check each line
+/// before tokenizing it: operands and operators have to alternate, and parentheses have to balance.
+fn check_line(src: &Source, line: &str) -> Result<(), ParseError> {
+    let mut depth = 0;
+    let mut expect_operand = true;
+    let mut rest = line.trim_start();
+    while let Some(c) = rest.chars().next() {
+        let mut len = c.len_utf8();
+        match c {
+            '(' if expect_operand => depth += 1,
+            ')' if !expect_operand && depth > 0 => depth -= 1,
+            '+' | '*' if !expect_operand => expect_operand = true,
+            '0'..='9' if expect_operand => {
+                len = rest
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(rest.len());
+                src.number::<i64>(&rest[..len])?;
+                expect_operand = false;
+            }
+            ')' if depth == 0 => return Err(src.error(rest, "unmatched ')'")),
+            '(' | ')' | '+' | '*' | '0'..='9' => {
+                return Err(src.error(rest, format!("unexpected {:?}", c)))
+            }
+            _ => return Err(src.error(rest, format!("unexpected character {:?}", c))),
+        }
+        rest = rest[len..].trim_start();
+    }
+    if expect_operand {
+        Err(src.error(rest, "expression ends where a number or '(' was expected"))
+    } else if depth > 0 {
+        Err(src.error(rest, "missing ')'"))
+    } else {
+        Ok(())
+    }
+}
+
+#[aoc_generator(day18)]
+pub fn parse(input: &str) -> Result<Vec<String>, ParseError> {
+    let src = Source::new(18, input);
+    input
+        .lines()
+        .map(|line| {
+            check_line(&src, line)?;
+            Ok(line.to_owned())
+        })
+        .collect()
 }
 
 #[aoc(day18, part1)]
-fn day1(input: &str) -> i64 {
-    input.lines().map(|line| evaluate_1(line)).sum()
+pub fn day1(input: &[String]) -> i64 {
+    input.iter().map(|line| evaluate_1(line)).sum()
 }
 
 #[aoc(day18, part2)]
-fn day2(input: &str) -> i64 {
-    input.lines().map(|line| evaluate_2(line)).sum()
+pub fn day2(input: &[String]) -> i64 {
+    input.iter().map(|line| evaluate_2(line)).sum()
+}
+
+pub struct Day18;
+
+impl Solution for Day18 {
+    type Input = Vec<String>;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        day1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        day2(input)
+    }
 }
@@ -1,5 +1,28 @@
 use std::collections::HashMap;
 
+use crate::error::{ParseError, Source};
+use crate::example::Example;
+use crate::lint::Check;
+use crate::solution::Solution;
+
+/// Part 2 only makes sense for inputs that have rules 8, 11, 31 and 42, which this one doesn't.
+pub const EXAMPLES: &[Example] = &[Example {
+    part: 1,
+    input: r#"0: 4 1 5
+1: 2 3 | 3 2
+2: 4 4 | 5 5
+3: 4 5 | 5 4
+4: "a"
+5: "b"
+
+ababbb
+bababa
+abbbab
+aaabbb
+aaaabbb"#,
+    expected: "2",
+}];
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Rule {
     Literal(String),
@@ -8,16 +31,20 @@ pub enum Rule {
 }
 
 impl Rule {
-    fn parse(input: &str) -> (Rule, Vec<usize>) {
+    fn parse(src: &Source, input: &str) -> Result<(Rule, Vec<usize>), ParseError> {
         let (deps, rule) = if let Some(left_quote) = input.find('"') {
-            let right_quote = input[left_quote + 1..].find('"').unwrap() + left_quote + 1;
+            let right_quote = input[left_quote + 1..]
+                .find('"')
+                .ok_or_else(|| src.error(&input[left_quote..], "missing closing '\"'"))?
+                + left_quote
+                + 1;
             (
                 vec![],
                 Rule::Literal(input[left_quote + 1..right_quote].to_owned()),
             )
         } else if let Some(pipe) = input.find('|') {
-            let (left_rules, mut left_deps) = Rule::parse(&input[..pipe]);
-            let (right_rules, right_deps) = Rule::parse(&input[pipe + 1..]);
+            let (left_rules, mut left_deps) = Rule::parse(src, &input[..pipe])?;
+            let (right_rules, right_deps) = Rule::parse(src, &input[pipe + 1..])?;
             for v in right_deps {
                 if !left_deps.contains(&v) {
                     left_deps.push(v);
@@ -28,37 +55,42 @@ impl Rule {
                 Rule::Option(Box::from(left_rules), Box::from(right_rules)),
             )
         } else {
-            let parts: Vec<_> = input
-                .trim()
-                .split(' ')
-                .map(|x| x.parse().unwrap())
-                .collect();
+            let parts: Vec<_> = src.numbers(input.trim(), ' ')?;
             (parts.clone(), Rule::Concat(parts))
         };
-        (rule, deps)
+        Ok((rule, deps))
     }
 }
 
-pub fn parse_line(input: &str) -> (usize, Rule, Vec<usize>) {
-    let colon_idx = input.find(':').unwrap();
-    let rule_num: usize = input[..colon_idx].parse().unwrap();
+pub fn parse_line(src: &Source, input: &str) -> Result<(usize, Rule, Vec<usize>), ParseError> {
+    let colon_idx = input.find(": ").ok_or_else(|| {
+        src.error(
+            input,
+            format!("expected \"<number>: <rule>\", found {:?}", input),
+        )
+    })?;
+    let rule_num: usize = src.number(&input[..colon_idx])?;
     let rest = &input[colon_idx + 2..];
-    let parsed = Rule::parse(rest);
-    (rule_num, parsed.0, parsed.1)
+    let parsed = Rule::parse(src, rest)?;
+    Ok((rule_num, parsed.0, parsed.1))
 }
 
 pub struct Input {
-    rules: HashMap<usize, Rule>,
-    deps: HashMap<usize, Vec<usize>>,
+    pub rules: HashMap<usize, Rule>,
+    /// The rules each rule refers to, directly.
+    pub deps: HashMap<usize, Vec<usize>>,
 }
 
-pub fn parse(input: &str) -> Input {
+/// Parses just the rules, i.e. the part of the input before the blank line.
+pub fn parse(src: &Source, input: &str) -> Result<Input, ParseError> {
     let mut ret = Input {
         rules: HashMap::new(),
         deps: HashMap::new(),
     };
+    let mut lines_and_deps = vec![];
     for line in input.lines() {
-        let (rule_num, rule, deps) = parse_line(line);
+        let (rule_num, rule, deps) = parse_line(src, line)?;
+        lines_and_deps.push((line, deps.clone()));
         ret.rules.insert(rule_num, rule);
         let current_deps = ret.deps.entry(rule_num).or_insert(vec![]);
         for d in deps {
@@ -67,20 +99,37 @@ pub fn parse(input: &str) -> Input {
             }
         }
     }
-    ret
+    // every rule a rule refers to had better exist, otherwise the matchers below panic
+    for (line, deps) in lines_and_deps {
+        if let Some(missing) = deps.iter().find(|d| !ret.rules.contains_key(d)) {
+            return Err(src.error(
+                line,
+                format!("refers to rule {}, which doesn't exist", missing),
+            ));
+        }
+    }
+    Ok(ret)
+}
+
+#[aoc_generator(day19)]
+pub fn gen(input: &str) -> Result<(Input, Vec<String>), ParseError> {
+    let src = Source::new(19, input);
+    let divider = input
+        .find("\n\n")
+        .ok_or_else(|| src.eof("expected a blank line between the rules and the messages"))?;
+    let rules = parse(&src, &input[..divider])?;
+    let messages = input[divider + 2..].lines().map(|x| x.to_owned()).collect();
+    Ok((rules, messages))
 }
 
 #[aoc(day19, part1)]
-pub fn part1(i: &str) -> i32 {
-    let divider = i.find("\n\n").unwrap();
-    let input = parse(&i[..divider]);
+pub fn part1((input, messages): &(Input, Vec<String>)) -> i32 {
     let mut cache = HashMap::new();
     part1::set_matches(0, &input.rules, &mut cache);
     let zero_matches = cache.get(&0).unwrap();
 
     let mut count = 0;
-    let body = &i[divider + 2..];
-    for line in body.lines() {
+    for line in messages {
         if zero_matches.contains(line) {
             count += 1;
         }
@@ -198,7 +247,7 @@ mod part2 {
                 let right = &test[j..];
                 if matches_for_42.contains(left)
                     && matches_for_31.contains(right)
-                    && (middle == "" || matches_11(middle, matches_for_42, matches_for_31))
+                    && (middle.is_empty() || matches_11(middle, matches_for_42, matches_for_31))
                 {
                     return true;
                 }
@@ -224,17 +273,14 @@ mod part2 {
 }
 
 #[aoc(day19, part2)]
-pub fn part2(i: &str) -> i32 {
-    let divider = i.find("\n\n").unwrap();
-    let input = parse(&i[..divider]);
+pub fn part2((input, messages): &(Input, Vec<String>)) -> i32 {
     let mut cache = HashMap::new();
 
     part1::set_matches(42, &input.rules, &mut cache);
     part1::set_matches(31, &input.rules, &mut cache);
 
     let mut count = 0;
-    let body = &i[divider + 2..];
-    for line in body.lines() {
+    for line in messages {
         if part2::matches_0(line, cache.get(&42).unwrap(), cache.get(&31).unwrap()) {
             count += 1;
         }
@@ -242,6 +288,116 @@ pub fn part2(i: &str) -> i32 {
     count
 }
 
+/// The first rule on a cycle reachable from `start`, if there is one.
+fn find_cycle(start: usize, deps: &HashMap<usize, Vec<usize>>) -> Option<usize> {
+    fn visit(
+        rule: usize,
+        deps: &HashMap<usize, Vec<usize>>,
+        on_path: &mut Vec<usize>,
+        done: &mut Vec<usize>,
+    ) -> Option<usize> {
+        if on_path.contains(&rule) {
+            return Some(rule);
+        }
+        if done.contains(&rule) {
+            return None;
+        }
+        on_path.push(rule);
+        for &dep in deps.get(&rule).into_iter().flatten() {
+            if let Some(cycle) = visit(dep, deps, on_path, done) {
+                return Some(cycle);
+            }
+        }
+        on_path.pop();
+        done.push(rule);
+        None
+    }
+    visit(start, deps, &mut vec![], &mut vec![])
+}
+
+/// What the plan described above `mod part2` takes for granted about the rules.
+pub fn assumptions(input: &Input) -> Vec<Check> {
+    let mut shape = vec![];
+    for rule in [0, 8, 11, 31, 42].iter() {
+        if !input.rules.contains_key(rule) {
+            shape.push(format!("there's no rule {}", rule));
+        }
+    }
+    match input.rules.get(&0) {
+        Some(Rule::Concat(parts)) if *parts == [8, 11] => {}
+        Some(Rule::Concat(parts)) => {
+            let parts: Vec<_> = parts.iter().map(|p| p.to_string()).collect();
+            shape.push(format!("rule 0 is \"{}\"", parts.join(" ")));
+        }
+        Some(_) => shape.push("rule 0 isn't a plain sequence of rules".to_owned()),
+        None => {}
+    }
+
+    let mut users: Vec<String> = vec![];
+    let mut rule_nums: Vec<_> = input.deps.keys().copied().collect();
+    rule_nums.sort_unstable();
+    for rule in rule_nums {
+        if rule == 0 {
+            continue;
+        }
+        for special in [8, 11].iter() {
+            if input.deps[&rule].contains(special) {
+                users.push(format!("rule {} refers to rule {}", rule, special));
+            }
+        }
+    }
+
+    let mut cycles = vec![];
+    for root in [42, 31].iter() {
+        if input.rules.contains_key(root) {
+            if let Some(rule) = find_cycle(*root, &input.deps) {
+                cycles.push(format!(
+                    "rule {} leads to a cycle through rule {}",
+                    root, rule
+                ));
+            }
+        }
+    }
+
+    vec![
+        Check::new(
+            "day19::part2",
+            "rules 8, 11, 31 and 42 exist, and rule 0 is \"8 11\"",
+            shape,
+        ),
+        Check::new(
+            "day19::part2",
+            "rules 8 and 11 are only used by rule 0",
+            users,
+        ),
+        Check::new(
+            "day19::part2",
+            "the rules under 42 and 31 have no cycles",
+            cycles,
+        ),
+    ]
+}
+
+pub struct Day19;
+
+impl Solution for Day19 {
+    type Input = (Input, Vec<String>);
+    type Part1 = i32;
+    type Part2 = i32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        gen(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::collections::HashSet;
@@ -256,25 +412,26 @@ mod test {
 3: 4 5 | 5 4
 4: "a"
 5: "b""#;
+        let src = Source::new(19, lines);
         let lines: Vec<_> = lines.lines().collect();
         assert_eq!(
-            parse_line(lines[0]),
-            (0, Rule::Concat(vec![4, 1, 5]), vec![4, 1, 5])
+            parse_line(&src, lines[0]),
+            Ok((0, Rule::Concat(vec![4, 1, 5]), vec![4, 1, 5]))
         );
         assert_eq!(
-            parse_line(lines[1]),
-            (
+            parse_line(&src, lines[1]),
+            Ok((
                 1,
                 Rule::Option(
                     Box::from(Rule::Concat(vec![2, 3])),
                     Box::from(Rule::Concat(vec![3, 2])),
                 ),
                 vec![2, 3]
-            )
+            ))
         );
         assert_eq!(
-            parse_line(lines[4]),
-            (4, Rule::Literal("a".to_owned()), vec![])
+            parse_line(&src, lines[4]),
+            Ok((4, Rule::Literal("a".to_owned()), vec![]))
         );
     }
 
@@ -286,7 +443,7 @@ mod test {
 3: 4 5 | 5 4
 4: "a"
 5: "b""#;
-        let input = parse(lines);
+        let input = parse(&Source::new(19, lines), lines).unwrap();
 
         let mut cache = HashMap::new();
         part1::set_matches(5, &input.rules, &mut cache);
@@ -346,4 +503,23 @@ mod test {
         assert!(!matches_11("bbb", &matches_for_42, &matches_for_31));
         assert!(!matches_11("baba", &matches_for_42, &matches_for_31));
     }
+
+    #[test]
+    fn test_assumptions() {
+        let failures = |rules: &str| -> Vec<Option<String>> {
+            let input = parse(&Source::new(19, rules), rules).unwrap();
+            assumptions(&input).into_iter().map(|c| c.failure).collect()
+        };
+        let fine = "0: 8 11\n8: 42\n11: 42 31\n42: \"a\"\n31: \"b\"";
+        assert_eq!(failures(fine), vec![None, None, None]);
+        let broken = "0: 8 11\n8: 42\n11: 42 31\n42: 8 | 43\n43: 42\n31: \"b\"";
+        assert_eq!(
+            failures(broken),
+            vec![
+                None,
+                Some("rule 42 refers to rule 8".to_owned()),
+                Some("rule 42 leads to a cycle through rule 42".to_owned())
+            ]
+        );
+    }
 }
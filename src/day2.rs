@@ -1,56 +1,250 @@
+use std::fmt;
+
 use regex::Regex;
 
+use crate::error::{ParseError, Source};
+use crate::example::Example;
+use crate::solution::Solution;
+
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: EXAMPLE,
+        expected: "2",
+    },
+    Example {
+        part: 2,
+        input: EXAMPLE,
+        expected: "1",
+    },
+];
+
+const EXAMPLE: &str = "1-3 a: abcde
+1-3 b: cdefg
+2-9 c: ccccccccc";
+
 #[derive(Debug)]
 pub struct Row {
-    min: usize,
-    max: usize,
-    letter: char,
-    password: String,
+    pub min: usize,
+    pub max: usize,
+    pub letter: char,
+    pub password: String,
+}
+
+impl fmt::Display for Row {
+    /// The row as it appears in the input.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{} {}: {}",
+            self.min, self.max, self.letter, self.password
+        )
+    }
 }
 
 #[aoc_generator(day2)]
-pub fn gen(input: &str) -> Vec<Row> {
+pub fn gen(input: &str) -> Result<Vec<Row>, ParseError> {
+    let src = Source::new(2, input);
     let mut ret = vec![];
-    let re = Regex::new(r"(\d+)-(\d+) (.): (.+)").unwrap();
+    let re = Regex::new(r"^(\d+)-(\d+) (.): (.+)$").unwrap();
     for line in input.lines() {
-        let captures = re.captures(line).unwrap();
+        let captures = re.captures(line).ok_or_else(|| {
+            src.error(
+                line,
+                format!(
+                    "expected a policy and password like \"1-3 a: abcde\", found {:?}",
+                    line
+                ),
+            )
+        })?;
         ret.push(Row {
-            min: captures[1].parse().unwrap(),
-            max: captures[2].parse().unwrap(),
+            min: src.number(captures.get(1).unwrap().as_str())?,
+            max: src.number(captures.get(2).unwrap().as_str())?,
             letter: captures[3].chars().next().unwrap(),
             password: captures[4].to_string(),
         })
     }
-    ret
+    Ok(ret)
 }
 
-#[aoc(day2, part1)]
-pub fn solve_1(input: &[Row]) -> i32 {
-    let mut ans = 0;
-    for row in input {
+/// What a policy made of a password, with the reason.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Valid(String),
+    Invalid(String),
+    /// The policy doesn't make sense for this password, e.g. it names a position past the end.
+    /// The password doesn't count as valid.
+    Malformed(String),
+}
+
+impl Verdict {
+    pub fn is_valid(&self) -> bool {
+        matches!(self, Verdict::Valid(_))
+    }
+
+    pub fn reason(&self) -> &str {
+        match self {
+            Verdict::Valid(reason) | Verdict::Invalid(reason) | Verdict::Malformed(reason) => {
+                reason
+            }
+        }
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Verdict::Valid(_) => "valid",
+            Verdict::Invalid(_) => "invalid",
+            Verdict::Malformed(_) => "malformed",
+        };
+        write!(f, "{}: {}", label, self.reason())
+    }
+}
+
+/// One way of reading the "1-3 a" part of a row. Closures taking a `&Row` are policies too,
+/// so a new reading doesn't need its own type.
+pub trait PasswordPolicy {
+    fn check(&self, row: &Row) -> Verdict;
+}
+
+impl<F: Fn(&Row) -> Verdict> PasswordPolicy for F {
+    fn check(&self, row: &Row) -> Verdict {
+        self(row)
+    }
+}
+
+/// Part 1's reading: the letter has to appear between `min` and `max` times.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CountPolicy;
+
+impl PasswordPolicy for CountPolicy {
+    fn check(&self, row: &Row) -> Verdict {
         let count = row.password.matches(row.letter).count();
+        let reason = format!(
+            "{:?} appears {} times, and it must appear {} to {} times",
+            row.letter, count, row.min, row.max
+        );
         if count >= row.min && count <= row.max {
-            ans += 1;
+            Verdict::Valid(reason)
+        } else {
+            Verdict::Invalid(reason)
         }
     }
-    ans
 }
 
-#[aoc(day2, part2)]
-pub fn solve_2(input: &[Row]) -> i32 {
-    let mut ans = 0;
-    for row in input {
-        let mut count = 0;
+/// Part 2's reading: the letter has to be at exactly one of the (1-based) positions `min` and
+/// `max`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PositionPolicy;
+
+impl PasswordPolicy for PositionPolicy {
+    fn check(&self, row: &Row) -> Verdict {
         let chars: Vec<_> = row.password.chars().collect();
-        if chars[row.min - 1] == row.letter {
-            count += 1;
+        for &position in [row.min, row.max].iter() {
+            if position == 0 || position > chars.len() {
+                return Verdict::Malformed(format!(
+                    "position {} isn't in the password, which has {} letters",
+                    position,
+                    chars.len()
+                ));
+            }
         }
-        if chars[row.max - 1] == row.letter {
-            count += 1;
+        let (first, second) = (chars[row.min - 1], chars[row.max - 1]);
+        let reason = format!(
+            "position {} is {:?} and position {} is {:?}, and exactly one must be {:?}",
+            row.min, first, row.max, second, row.letter
+        );
+        if (first == row.letter) != (second == row.letter) {
+            Verdict::Valid(reason)
+        } else {
+            Verdict::Invalid(reason)
         }
-        if count == 1 {
+    }
+}
+
+/// Each row with what the policy made of it.
+pub fn check_all<'a, P: PasswordPolicy + ?Sized>(
+    policy: &P,
+    rows: &'a [Row],
+) -> Vec<(&'a Row, Verdict)> {
+    rows.iter().map(|row| (row, policy.check(row))).collect()
+}
+
+pub fn count_valid<P: PasswordPolicy + ?Sized>(policy: &P, rows: &[Row]) -> i32 {
+    let mut ans = 0;
+    for (row, verdict) in check_all(policy, rows) {
+        debug!("{}: {}", row, verdict);
+        if verdict.is_valid() {
             ans += 1;
         }
     }
     ans
 }
+
+#[aoc(day2, part1)]
+pub fn solve_1(input: &[Row]) -> i32 {
+    count_valid(&CountPolicy, input)
+}
+
+#[aoc(day2, part2)]
+pub fn solve_2(input: &[Row]) -> i32 {
+    count_valid(&PositionPolicy, input)
+}
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    type Input = Vec<Row>;
+    type Part1 = i32;
+    type Part2 = i32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        gen(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        solve_1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        solve_2(input)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_policies() {
+        let rows = gen(EXAMPLE).unwrap();
+        let verdicts: Vec<bool> = check_all(&CountPolicy, &rows)
+            .into_iter()
+            .map(|(_, v)| v.is_valid())
+            .collect();
+        assert_eq!(verdicts, vec![true, false, true]);
+        assert_eq!(
+            PositionPolicy.check(&rows[2]),
+            Verdict::Invalid(
+                "position 2 is 'c' and position 9 is 'c', and exactly one must be 'c'".to_owned()
+            )
+        );
+
+        let rows = gen("0-2 a: ab\n1-5 a: abc").unwrap();
+        assert!(check_all(&PositionPolicy, &rows)
+            .iter()
+            .all(|(_, v)| matches!(v, Verdict::Malformed(_))));
+        assert_eq!(count_valid(&PositionPolicy, &rows), 0);
+
+        // Any closure will do as a policy
+        let starts_with = |row: &Row| {
+            if row.password.starts_with(row.letter) {
+                Verdict::Valid("starts with the letter".to_owned())
+            } else {
+                Verdict::Invalid("doesn't start with the letter".to_owned())
+            }
+        };
+        assert_eq!(count_valid(&starts_with, &rows), 2);
+    }
+}
@@ -1,7 +1,133 @@
-#![allow(clippy::ptr_arg, clippy::clippy::needless_range_loop)]
+#![allow(clippy::ptr_arg, clippy::needless_range_loop)]
 
 use std::collections::HashMap;
 
+use crate::error::{ParseError, Source};
+use crate::example::Example;
+use crate::lint::Check;
+use crate::solution::Solution;
+
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: EXAMPLE,
+        expected: "20899048083289",
+    },
+    Example {
+        part: 2,
+        input: EXAMPLE,
+        expected: "273",
+    },
+];
+
+const EXAMPLE: &str = "Tile 2311:
+..##.#..#.
+##..#.....
+#...##..#.
+####.#...#
+##.##.###.
+##...#.###
+.#.#.#..##
+..#....#..
+###...#.#.
+..###..###
+
+Tile 1951:
+#.##...##.
+#.####...#
+.....#..##
+#...######
+.##.#....#
+.###.#####
+###.##.##.
+.###....#.
+..#.#..#.#
+#...##.#..
+
+Tile 1171:
+####...##.
+#..##.#..#
+##.#..#.#.
+.###.####.
+..###.####
+.##....##.
+.#...####.
+#.##.####.
+####..#...
+.....##...
+
+Tile 1427:
+###.##.#..
+.#..#.##..
+.#.##.#..#
+#.#.#.##.#
+....#...##
+...##..##.
+...#.#####
+.#.####.#.
+..#..###.#
+..##.#..#.
+
+Tile 1489:
+##.#.#....
+..##...#..
+.##..##...
+..#...#...
+#####...#.
+#..#.#.#.#
+...#.#.#..
+##.#...##.
+..##.##.##
+###.##.#..
+
+Tile 2473:
+#....####.
+#..#.##...
+#.##..#...
+######.#.#
+.#...#.#.#
+.#########
+.###.#..#.
+########.#
+##...##.#.
+..###.#.#.
+
+Tile 2971:
+..#.#....#
+#...###...
+#.#.###...
+##.##..#..
+.#####..##
+.#..####.#
+#..#.#..#.
+..####.###
+..#.#.###.
+...#.#.#.#
+
+Tile 2729:
+...#.#.#.#
+####.#....
+..#.#.....
+....#..#.#
+.##..##.#.
+.#.####...
+####.#.#..
+##.####...
+##..#.##..
+#.##...##.
+
+Tile 3079:
+#.#.#####.
+.#..######
+..#.......
+######....
+####.#..#.
+.#...#.##.
+#.#####.##
+..#.###...
+..#.......
+..#.###...";
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
 enum Edge {
     Top,
@@ -12,9 +138,10 @@ enum Edge {
 
 const EDGES: [Edge; 4] = [Edge::Top, Edge::Bottom, Edge::Left, Edge::Right];
 
-type Tile = Vec<Vec<char>>;
-#[allow(unused)]
-fn tile_to_str(tile: &Tile) -> String {
+/// A tile (or the whole image, once it's put together), one row of '#' and '.' per Vec.
+pub type Tile = Vec<Vec<char>>;
+
+pub fn tile_to_str(tile: &Tile) -> String {
     tile.iter() // Iterator<Item=&Vec<Char>>
         .map(|row| row.iter().collect::<String>()) // Iterator<Item=String>
         .collect::<Vec<_>>() // Vec<String> (since join is a slice method, we need this collect)
@@ -45,18 +172,42 @@ fn edges_match(e1: &[char], e2: &[char]) -> Result<bool, ()> {
     }
 }
 
-fn parse(input: &str) -> HashMap<usize, Tile> {
+/// Everything below assumes every tile is 10x10, so we check that here.
+#[aoc_generator(day20)]
+pub fn parse(input: &str) -> Result<HashMap<usize, Tile>, ParseError> {
+    let src = Source::new(20, input);
     let mut map = HashMap::new();
     for group in input.split("\n\n") {
-        let first_line = group.find('\n').unwrap();
-        let tile_number: usize = group[5..first_line - 1].parse().unwrap();
-        let tile = group[first_line + 1..]
-            .lines()
-            .map(|line| line.chars().collect())
-            .collect();
-        map.insert(tile_number, tile);
+        let mut lines = group.lines();
+        let header = lines.next().unwrap_or(group);
+        if !header.starts_with("Tile ") || !header.ends_with(':') {
+            return Err(src.error(
+                header,
+                format!("expected a header like \"Tile 2311:\", found {:?}", header),
+            ));
+        }
+        let tile_number: usize = src.number(&header[5..header.len() - 1])?;
+        let mut tile: Tile = vec![];
+        for line in lines {
+            if line.len() != 10 || line.contains(|c| c != '#' && c != '.') {
+                return Err(src.error(line, "expected a row of 10 '#'s and '.'s"));
+            }
+            tile.push(line.chars().collect());
+        }
+        if tile.len() != 10 {
+            return Err(src.error(
+                header,
+                format!("tile {} has {} rows, not 10", tile_number, tile.len()),
+            ));
+        }
+        if map.insert(tile_number, tile).is_some() {
+            return Err(src.error(
+                header,
+                format!("there are two tiles numbered {}", tile_number),
+            ));
+        }
     }
-    map
+    Ok(map)
 }
 
 /// I have a feeling that this problem is actually easier than it could be.
@@ -68,8 +219,7 @@ fn parse(input: &str) -> HashMap<usize, Tile> {
 /// other edge, which means the logic of building a rectangle is actually much simpler.
 /// Let's see if that guess pans out for me.
 #[aoc(day20, part1)]
-fn part1(input: &str) -> usize {
-    let tiles = parse(input);
+pub fn part1(tiles: &HashMap<usize, Tile>) -> usize {
     // an "edge" is a tuple (first-tile, which-edge, second-tile, second-edge, reflect?)
     // tiles are tile numbers, which-edge is top, bottom, left, right
     let mut answer = 1;
@@ -82,18 +232,17 @@ fn part1(input: &str) -> usize {
             }
             for test_edge in EDGES.iter() {
                 for edge in EDGES.iter() {
-                    if edges_match(&get_edge(test_edge, &test_tile), &get_edge(edge, &tile)).is_ok()
-                    {
+                    if edges_match(&get_edge(test_edge, test_tile), &get_edge(edge, tile)).is_ok() {
                         n_matches += 1;
                     }
                 }
             }
         }
-        // Yep, as expected, this print statement prints out 4 for most things, 3 for
+        // Yep, as expected, this prints out 4 for most things, 3 for
         // a few things, and 2 for exactly four things
-        println!("for tile {} found {} matches", test_tile_num, n_matches);
+        debug!("for tile {} found {} matches", test_tile_num, n_matches);
         if n_matches == 2 {
-            println!("found corner (2 matches): {}", test_tile_num);
+            info!("found corner (2 matches): {}", test_tile_num);
             answer *= test_tile_num;
         }
     }
@@ -120,8 +269,8 @@ fn part1(input: &str) -> usize {
 // accomodate that each edge has TWO tiles, and they may be flipped as well, and we still need to be able
 // to extract edges from tiles so we can continue the lookup chain.
 #[aoc(day20, part2)]
-pub fn part2(input: &str) -> usize {
-    let mut tiles = parse(input);
+pub fn part2(input: &HashMap<usize, Tile>) -> usize {
+    let mut tiles = input.clone();
 
     let mut rows = vec![];
     let mut first_row = vec![];
@@ -135,15 +284,11 @@ pub fn part2(input: &str) -> usize {
     while !tiles.is_empty() {
         let prev = &rows.last().unwrap()[0];
         let seed = find_bottom_match(&mut tiles, prev).unwrap();
-        // 	println!("================================================================================
-        // First of prev row, and seed:
-        // {}
-
-        // {}
-        // ================================================================================",
-        // 		 tile_to_str(prev),
-        // 		 tile_to_str(&seed),
-        // 	);
+        trace!(
+            "first of previous row:\n{}\nseed:\n{}",
+            tile_to_str(prev),
+            tile_to_str(&seed)
+        );
         let mut row = vec![seed];
         while let Some(tile) = find_right_match(&mut tiles, row.last().unwrap()) {
             row.push(tile);
@@ -159,40 +304,40 @@ pub fn part2(input: &str) -> usize {
     // but it was more complicated, error prone, and not any more efficient
     // than just doing it this way.
     if let Some(answer) = search_for_monsters(&mut map.clone()) {
-        println!("found answer in base");
+        info!("found answer in base");
         return answer;
     }
     if let Some(answer) = search_for_monsters(&mut rotated_90(&map)) {
-        println!("found answer in 90-rotated");
+        info!("found answer in 90-rotated");
         return answer;
     }
 
     if let Some(answer) = search_for_monsters(&mut rotated_180(&map)) {
-        println!("found answer in 180-rotated");
+        info!("found answer in 180-rotated");
         return answer;
     }
 
     if let Some(answer) = search_for_monsters(&mut rotated_270(&map)) {
-        println!("found answer in 270-rotated");
+        info!("found answer in 270-rotated");
         return answer;
     }
     let map = flipped_left_right(&map);
     if let Some(answer) = search_for_monsters(&mut map.clone()) {
-        println!("found answer in base flipped");
+        info!("found answer in base flipped");
         return answer;
     }
     if let Some(answer) = search_for_monsters(&mut rotated_90(&map)) {
-        println!("found answer in 90-rotated flipped");
+        info!("found answer in 90-rotated flipped");
         return answer;
     }
 
     if let Some(answer) = search_for_monsters(&mut rotated_180(&map)) {
-        println!("found answer in 180-rotated flipped");
+        info!("found answer in 180-rotated flipped");
         return answer;
     }
 
     if let Some(answer) = search_for_monsters(&mut rotated_270(&map)) {
-        println!("found answer in 270-rotated flipped");
+        info!("found answer in 270-rotated flipped");
         return answer;
     }
 
@@ -211,7 +356,7 @@ fn find_top_left_corner(tiles: &mut HashMap<usize, Tile>) -> Tile {
                 }
                 for test_edge in EDGES.iter() {
                     for edge in EDGES.iter() {
-                        if edges_match(&get_edge(test_edge, &test_tile), &get_edge(edge, &tile))
+                        if edges_match(&get_edge(test_edge, test_tile), &get_edge(edge, tile))
                             .is_ok()
                         {
                             matches.push(*test_edge);
@@ -228,7 +373,7 @@ fn find_top_left_corner(tiles: &mut HashMap<usize, Tile>) -> Tile {
         .unwrap();
     edges.sort();
     let tile = tiles.remove(&some_corner).unwrap();
-    // println!("{}", tile_to_str(&tile));
+    trace!("top left corner:\n{}", tile_to_str(&tile));
     if edges == vec![Edge::Top, Edge::Right] {
         rotated_90(&tile)
     } else if edges == vec![Edge::Bottom, Edge::Left] {
@@ -247,8 +392,8 @@ fn find_right_match(tiles: &mut HashMap<usize, Tile>, base_tile: &Tile) -> Optio
             let test_tile = tiles.get(test_tile_num).unwrap();
             for edge in EDGES.iter() {
                 if let Ok(flip) = edges_match(
-                    &get_edge(&Edge::Right, &base_tile),
-                    &get_edge(edge, &test_tile),
+                    &get_edge(&Edge::Right, base_tile),
+                    &get_edge(edge, test_tile),
                 ) {
                     return Some((*test_tile_num, *edge, flip));
                 }
@@ -283,8 +428,8 @@ fn find_bottom_match(tiles: &mut HashMap<usize, Tile>, base_tile: &Tile) -> Opti
         let test_tile = tiles.get(test_tile_num).unwrap();
         for edge in EDGES.iter() {
             if let Ok(flip) = edges_match(
-                &get_edge(&Edge::Bottom, &base_tile),
-                &get_edge(edge, &test_tile),
+                &get_edge(&Edge::Bottom, base_tile),
+                &get_edge(edge, test_tile),
             ) {
                 return Some((*test_tile_num, *edge, flip));
             }
@@ -314,7 +459,7 @@ fn find_bottom_match(tiles: &mut HashMap<usize, Tile>, base_tile: &Tile) -> Opti
 }
 
 // all rotations are clockwise
-fn rotated_90(tile: &Tile) -> Tile {
+pub fn rotated_90(tile: &Tile) -> Tile {
     let mut new_tile = vec![];
     let l = tile.len();
     for i in 0..l {
@@ -328,7 +473,7 @@ fn rotated_90(tile: &Tile) -> Tile {
 }
 
 // all rotations are clockwise
-fn rotated_180(tile: &Tile) -> Tile {
+pub fn rotated_180(tile: &Tile) -> Tile {
     let mut new_tile = vec![];
     let l = tile.len();
     for i in 0..l {
@@ -342,7 +487,7 @@ fn rotated_180(tile: &Tile) -> Tile {
 }
 
 // all rotations are clockwise
-fn rotated_270(tile: &Tile) -> Tile {
+pub fn rotated_270(tile: &Tile) -> Tile {
     let mut new_tile = vec![];
     let l = tile.len();
     for i in 0..l {
@@ -355,7 +500,7 @@ fn rotated_270(tile: &Tile) -> Tile {
     new_tile
 }
 
-fn flipped_left_right(tile: &Tile) -> Tile {
+pub fn flipped_left_right(tile: &Tile) -> Tile {
     let mut new_tile = vec![];
     let l = tile.len();
     for i in 0..l {
@@ -368,7 +513,7 @@ fn flipped_left_right(tile: &Tile) -> Tile {
     new_tile
 }
 
-fn flipped_top_bottom(tile: &Tile) -> Tile {
+pub fn flipped_top_bottom(tile: &Tile) -> Tile {
     let mut new_tile = vec![];
     let l = tile.len();
     for i in 0..l {
@@ -382,7 +527,7 @@ fn flipped_top_bottom(tile: &Tile) -> Tile {
 }
 
 // assumption: all tiles are 10x10, and grid is rectangular
-fn unify(grid: &Vec<Vec<Tile>>) -> Vec<Vec<char>> {
+pub fn unify(grid: &Vec<Vec<Tile>>) -> Vec<Vec<char>> {
     let tile_rows = grid[0][0].len(); // aka 10.
     let n_rows = grid.len() * (tile_rows - 2);
     let mut rows = Vec::with_capacity(n_rows);
@@ -405,7 +550,7 @@ fn unify(grid: &Vec<Vec<Tile>>) -> Vec<Vec<char>> {
 // it searches point by point over the map for a sea monster whose top left is at that location
 // it mutates map by setting any squares that have sea monster to O, as describd in the problem.
 // This is not essential, it could take an immutable map and track the Os separately, but lazy.
-fn search_for_monsters(map: &mut Vec<Vec<char>>) -> Option<usize> {
+pub fn search_for_monsters(map: &mut Vec<Vec<char>>) -> Option<usize> {
     let height = map.len();
     let width = map[0].len();
 
@@ -461,10 +606,121 @@ fn search_for_monsters(map: &mut Vec<Vec<char>>) -> Option<usize> {
     if !monsters {
         None
     } else {
-        return Some(
+        Some(
             map.iter()
                 .map(|row| row.iter().filter(|&x| *x == '#').count())
                 .sum(),
+        )
+    }
+}
+
+/// Both parts find corners by counting matched edges, and part 2 places each tile next to
+/// the only one that matches it, so an edge that could go two ways breaks both.
+pub fn assumptions(tiles: &HashMap<usize, Tile>) -> Vec<Check> {
+    let mut ambiguous = vec![];
+    let mut corners = vec![];
+    let mut numbers: Vec<_> = tiles.keys().copied().collect();
+    numbers.sort_unstable();
+    for &number in &numbers {
+        let mut matched_edges = 0;
+        for edge in EDGES.iter() {
+            let mut matches = vec![];
+            for &other in &numbers {
+                if other == number {
+                    continue;
+                }
+                for other_edge in EDGES.iter() {
+                    let e1 = get_edge(edge, &tiles[&number]);
+                    let e2 = get_edge(other_edge, &tiles[&other]);
+                    if edges_match(&e1, &e2).is_ok() {
+                        matches.push(format!("{} {:?}", other, other_edge));
+                    }
+                }
+            }
+            if matches.len() > 1 {
+                ambiguous.push(format!(
+                    "tile {}'s {:?} edge matches {}",
+                    number,
+                    edge,
+                    matches.join(", ")
+                ));
+            }
+            if !matches.is_empty() {
+                matched_edges += 1;
+            }
+        }
+        if matched_edges == 2 {
+            corners.push(number);
+        }
+    }
+    let corner_count = if corners.len() == 4 {
+        vec![]
+    } else {
+        vec![format!(
+            "found {} tiles with two matched edges: {:?}",
+            corners.len(),
+            corners
+        )]
+    };
+    vec![
+        Check::new(
+            "day20::part1",
+            "each edge matches at most one edge of another tile",
+            ambiguous,
+        ),
+        Check::new(
+            "day20::part1",
+            "exactly four tiles have two matched edges",
+            corner_count,
+        ),
+    ]
+}
+
+pub struct Day20;
+
+impl Solution for Day20 {
+    type Input = HashMap<usize, Tile>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        part2(input)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_assumptions() {
+        let checks = assumptions(&parse(EXAMPLE).unwrap());
+        assert!(checks.iter().all(Check::passed), "{:?}", checks);
+
+        // Just the top row of the example: the middle tile is the only one with two matches
+        let row: Vec<_> = EXAMPLE
+            .split("\n\n")
+            .filter(|tile| {
+                ["Tile 1951:", "Tile 2311:", "Tile 3079:"]
+                    .iter()
+                    .any(|t| tile.starts_with(t))
+            })
+            .collect();
+        let failures: Vec<_> = assumptions(&parse(&row.join("\n\n")).unwrap())
+            .into_iter()
+            .filter_map(|c| c.failure)
+            .collect();
+        assert_eq!(
+            failures,
+            vec!["found 1 tiles with two matched edges: [2311]"]
         );
     }
 }
@@ -1,15 +1,43 @@
 use std::collections::{HashMap, HashSet};
 
-struct Line {
-    ingredients: Vec<String>,
-    allergens: Vec<String>,
+use crate::error::{ParseError, Source};
+use crate::example::Example;
+use crate::lint::{self, Check};
+use crate::solution::Solution;
+
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: EXAMPLE,
+        expected: "5",
+    },
+    Example {
+        part: 2,
+        input: EXAMPLE,
+        expected: "mxmxvkd,sqjhc,fvjkl",
+    },
+];
+
+pub struct Line {
+    pub ingredients: Vec<String>,
+    pub allergens: Vec<String>,
 }
 
-fn parse(input: &str) -> Vec<Line> {
+#[aoc_generator(day21)]
+pub fn parse(input: &str) -> Result<Vec<Line>, ParseError> {
+    let src = Source::new(21, input);
     input
         .lines()
         .map(|line| {
-            let paren_index = line.find('(').unwrap();
+            let paren_index = match line.find(" (contains ") {
+                Some(i) if line.ends_with(')') => i + 1,
+                _ => {
+                    return Err(src.error(
+                        line,
+                        "expected a food like \"<ingredients> (contains <allergens>)\"",
+                    ))
+                }
+            };
             let ingredients = line[..paren_index]
                 .trim()
                 .split(' ')
@@ -22,15 +50,14 @@ fn parse(input: &str) -> Vec<Line> {
                 .split(", ")
                 .map(|x| x.to_owned())
                 .collect();
-            Line {
+            Ok(Line {
                 ingredients,
                 allergens,
-            }
+            })
         })
         .collect()
 }
 
-#[allow(dead_code)]
 const EXAMPLE: &str = "mxmxvkd kfcds sqjhc nhms (contains dairy, fish)
 trh fvjkl sbzzf mxmxvkd (contains dairy)
 sqjhc fvjkl (contains soy)
@@ -40,7 +67,7 @@ sqjhc mxmxvkd sbzzf (contains fish)";
 /// such that we can be 1005 sure that the correct ingredients is somewhere in the value for that key
 /// We'll do this by taking, for each allergen, the intersection of all the ingredient lists such that
 /// the recipe contains that allergen
-fn initial_pass(lines: &[Line]) -> HashMap<String, HashSet<String>> {
+pub fn initial_pass(lines: &[Line]) -> HashMap<String, HashSet<String>> {
     let mut possible_ingredients_per_allergen: HashMap<String, HashSet<String>> = HashMap::new();
     for line in lines {
         for allergen in line.allergens.iter() {
@@ -64,22 +91,20 @@ fn initial_pass(lines: &[Line]) -> HashMap<String, HashSet<String>> {
 }
 
 #[aoc(day21, part1)]
-pub fn part1(input: &str) -> usize {
-    // let input = EXAMPLE;
-    let lines = parse(input);
-    let possible_ingredients_per_allergen: HashMap<String, HashSet<String>> = initial_pass(&lines);
-    // at this point, as per this print statement, there's still a bunch of possibilities
+pub fn part1(lines: &[Line]) -> usize {
+    let possible_ingredients_per_allergen: HashMap<String, HashSet<String>> = initial_pass(lines);
+    // at this point, as per this debug output, there's still a bunch of possibilities
     // my guess is part 2 will give us more info to narrow it down
-    println!("{:?}", possible_ingredients_per_allergen);
+    debug!("possibilities: {:?}", possible_ingredients_per_allergen);
     // but we can maybe solve part 1 by iterating over all ingreds and finding the ones
     // that don't appear in any value in the hashmap
     let mut all_possibilites = HashSet::new();
     for p in possible_ingredients_per_allergen.values() {
         all_possibilites.extend(p);
     }
-    println!("{:?}", all_possibilites);
+    debug!("all possibilities: {:?}", all_possibilites);
     let mut answer = 0;
-    for line in &lines {
+    for line in lines {
         for ingred in &line.ingredients {
             if !all_possibilites.contains(ingred) {
                 answer += 1;
@@ -94,11 +119,9 @@ pub fn part1(input: &str) -> usize {
 /// has only one possibility, assigning it that one, then removing that ingredient from the
 /// other allergens
 #[aoc(day21, part2)]
-pub fn part2(input: &str) -> String {
-    // let input = EXAMPLE;
-    let lines = parse(input);
+pub fn part2(lines: &[Line]) -> String {
     let mut possible_ingredients_per_allergen: HashMap<String, HashSet<String>> =
-        initial_pass(&lines);
+        initial_pass(lines);
     let mut ingredients = HashMap::new();
     while !possible_ingredients_per_allergen.is_empty() {
         let (next_allergen, ingred) = possible_ingredients_per_allergen
@@ -114,7 +137,7 @@ pub fn part2(input: &str) -> String {
                 }
             })
             .unwrap();
-        println!("assigning {} to {}", &ingred, &next_allergen);
+        info!("assigning {} to {}", &ingred, &next_allergen);
         possible_ingredients_per_allergen.remove(&next_allergen);
         for value in possible_ingredients_per_allergen.values_mut() {
             value.remove(&ingred);
@@ -123,10 +146,61 @@ pub fn part2(input: &str) -> String {
     }
     let mut ingredients: Vec<_> = ingredients.into_iter().collect();
     ingredients.sort_unstable_by_key(|pair| pair.0.clone());
-    println!("{:?}", ingredients);
+    debug!("{:?}", ingredients);
     ingredients
         .into_iter()
         .map(|pair| pair.1)
         .collect::<Vec<_>>()
         .join(",")
 }
+
+pub fn assumptions(lines: &[Line]) -> Vec<Check> {
+    let leftover = lint::eliminate(initial_pass(lines));
+    vec![Check::new(
+        "day21::part2",
+        "there's always an allergen with exactly one candidate ingredient left",
+        lint::unresolved(&leftover),
+    )]
+}
+
+pub struct Day21;
+
+impl Solution for Day21 {
+    type Input = Vec<Line>;
+    type Part1 = usize;
+    type Part2 = String;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        part2(input)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_assumptions() {
+        let checks = assumptions(&parse(EXAMPLE).unwrap());
+        assert!(checks.iter().all(Check::passed), "{:?}", checks);
+
+        // a and b always turn up together, so there's no telling which has which allergen
+        let lines = parse("a b (contains x, y)\na b (contains x)").unwrap();
+        let failures: Vec<_> = assumptions(&lines)
+            .into_iter()
+            .filter_map(|c| c.failure)
+            .collect();
+        assert_eq!(
+            failures,
+            vec!["x could be any of a, b; y could be any of a, b"]
+        );
+    }
+}
@@ -1,18 +1,51 @@
 use std::collections::{HashSet, VecDeque};
 
-fn parse(input: &str) -> (VecDeque<usize>, VecDeque<usize>) {
-    let divider = input.find("\n\n").unwrap();
+use crate::error::{ParseError, Source};
+use crate::example::Example;
+use crate::solution::Solution;
+
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: EXAMPLE,
+        expected: "306",
+    },
+    Example {
+        part: 2,
+        input: EXAMPLE,
+        expected: "291",
+    },
+];
+
+#[aoc_generator(day22)]
+pub fn parse(input: &str) -> Result<(VecDeque<usize>, VecDeque<usize>), ParseError> {
+    let src = Source::new(22, input);
+    let divider = input
+        .find("\n\n")
+        .ok_or_else(|| src.eof("expected a blank line between the two players' decks"))?;
     let player1 = &input[..divider];
     let player2 = &input[divider + 2..];
 
-    (parse_player(player1), parse_player(player2))
+    Ok((
+        parse_player(&src, player1, "Player 1:")?,
+        parse_player(&src, player2, "Player 2:")?,
+    ))
 }
 
-fn parse_player(input: &str) -> VecDeque<usize> {
-    input.lines().skip(1).map(|x| x.parse().unwrap()).collect()
+fn parse_player(src: &Source, input: &str, header: &str) -> Result<VecDeque<usize>, ParseError> {
+    let mut lines = input.lines();
+    if lines.next() != Some(header) {
+        return Err(src.error(input, format!("expected {:?}", header)));
+    }
+    let deck: VecDeque<usize> = lines.map(|x| src.number(x)).collect::<Result<_, _>>()?;
+    if deck.is_empty() {
+        return Err(src.error(input, "deck is empty"));
+    }
+    Ok(deck)
 }
 
-fn step(p1: &mut VecDeque<usize>, p2: &mut VecDeque<usize>) -> bool {
+/// Plays one round of Combat, returning whether the game is over.
+pub fn step(p1: &mut VecDeque<usize>, p2: &mut VecDeque<usize>) -> bool {
     let c1 = p1.pop_front().unwrap();
     let c2 = p2.pop_front().unwrap();
     if c1 < c2 {
@@ -26,14 +59,13 @@ fn step(p1: &mut VecDeque<usize>, p2: &mut VecDeque<usize>) -> bool {
     }
 }
 
-fn score(deck: &VecDeque<usize>) -> usize {
+pub fn score(deck: &VecDeque<usize>) -> usize {
     deck.iter()
         .enumerate()
         .map(|(i, &card)| card * (deck.len() - i))
         .sum()
 }
 
-#[allow(dead_code)]
 const EXAMPLE: &str = "Player 1:
 9
 2
@@ -49,15 +81,10 @@ Player 2:
 10";
 
 #[aoc(day22, part1)]
-pub fn part1(input: &str) -> usize {
-    // let input = EXAMPLE;
-    let (mut p1, mut p2) = parse(input);
-    // println!("{:?}", p1);
-    // println!("{:?}", p2);
+pub fn part1(input: &(VecDeque<usize>, VecDeque<usize>)) -> usize {
+    let (mut p1, mut p2) = input.clone();
     while !step(&mut p1, &mut p2) {
-        // println!("");
-        // println!("{:?}", p1);
-        // println!("{:?}", p2);
+        trace!("P1: {:?}, P2: {:?}", p1, p2);
     }
     if p1.is_empty() {
         score(&p2)
@@ -66,37 +93,37 @@ pub fn part1(input: &str) -> usize {
     }
 }
 
-enum GameResult {
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum GameResult {
     P1Game,
     P2Game,
 }
 
-fn play_recursive(p1: &mut VecDeque<usize>, p2: &mut VecDeque<usize>) -> GameResult {
+/// Plays a game of Recursive Combat to the end, leaving the winner with all the cards.
+pub fn play_recursive(p1: &mut VecDeque<usize>, p2: &mut VecDeque<usize>) -> GameResult {
     let mut positions = HashSet::new();
     loop {
-        // println!();
-        // println!("P1: {:?}", p1);
-        // println!("P2: {:?}", p2);
+        trace!("P1: {:?}, P2: {:?}", p1, p2);
         if positions.contains(&(p1.clone(), p2.clone())) {
-            // println!("position occured, P1 wins game");
+            trace!("position occured, P1 wins game");
             return GameResult::P1Game;
         }
         positions.insert((p1.clone(), p2.clone()));
         let c1 = p1.pop_front().unwrap();
         let c2 = p2.pop_front().unwrap();
         if c1 <= p1.len() && c2 <= p2.len() {
-            // println!("entering subgame");
+            trace!("entering subgame");
             let mut new_p1 = p1.iter().take(c1).copied().collect();
             let mut new_p2 = p2.iter().take(c2).copied().collect();
             let r = play_recursive(&mut new_p1, &mut new_p2);
             match r {
                 GameResult::P1Game => {
-                    // println!("p1 wins subgame");
+                    trace!("p1 wins subgame");
                     p1.push_back(c1);
                     p1.push_back(c2);
                 }
                 GameResult::P2Game => {
-                    // println!("p2 wins subgame");
+                    trace!("p2 wins subgame");
                     p2.push_back(c2);
                     p2.push_back(c1);
                 }
@@ -117,14 +144,31 @@ fn play_recursive(p1: &mut VecDeque<usize>, p2: &mut VecDeque<usize>) -> GameRes
 }
 
 #[aoc(day22, part2)]
-pub fn part2(input: &str) -> usize {
-    // let input = EXAMPLE;
-    let (mut p1, mut p2) = parse(input);
-    // println!("{:?}", p1);
-    // println!("{:?}", p2);
+pub fn part2(input: &(VecDeque<usize>, VecDeque<usize>)) -> usize {
+    let (mut p1, mut p2) = input.clone();
     let r = play_recursive(&mut p1, &mut p2);
     match r {
         GameResult::P1Game => score(&p1),
         GameResult::P2Game => score(&p2),
     }
 }
+
+pub struct Day22;
+
+impl Solution for Day22 {
+    type Input = (VecDeque<usize>, VecDeque<usize>);
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        part2(input)
+    }
+}
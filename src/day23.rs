@@ -1,12 +1,47 @@
 use std::convert::TryInto;
 
-#[aoc(day23, part1)]
-pub fn part1(input: &str) -> String {
-    #[allow(dead_code)]
-    const EXAMPLE: &str = "389125467";
-    // let input = EXAMPLE;
+use crate::error::{ParseError, Source};
+use crate::example::Example;
+use crate::solution::Solution;
+
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: EXAMPLE,
+        expected: "67384529",
+    },
+    Example {
+        part: 2,
+        input: EXAMPLE,
+        expected: "149245887792",
+    },
+];
+
+const EXAMPLE: &str = "389125467";
+
+/// Both parts assume the labels are exactly the digits 1 to 9, each once.
+#[aoc_generator(day23)]
+pub fn parse(input: &str) -> Result<Vec<u32>, ParseError> {
+    let src = Source::new(23, input);
+    let mut cups = vec![];
+    for (i, c) in input.char_indices() {
+        match c.to_digit(10) {
+            Some(d) if d != 0 && !cups.contains(&d) => cups.push(d),
+            Some(_) => {
+                return Err(src.error(&input[i..], format!("{} isn't a new label from 1 to 9", c)))
+            }
+            None => return Err(src.error(&input[i..], format!("expected a digit, found {:?}", c))),
+        }
+    }
+    if cups.len() != 9 {
+        return Err(src.eof(format!("expected 9 cups, found {}", cups.len())));
+    }
+    Ok(cups)
+}
 
-    let mut cups: Vec<u32> = input.chars().map(|x| x.to_digit(10).unwrap()).collect();
+#[aoc(day23, part1)]
+pub fn part1(input: &[u32]) -> String {
+    let mut cups = input.to_vec();
     let mut current_cup = cups[0];
     for _ in 0..100 {
         current_cup = step(&mut cups, current_cup);
@@ -23,13 +58,11 @@ pub fn part1(input: &str) -> String {
 }
 
 fn step(cups: &mut Vec<u32>, current_cup: u32) -> u32 {
-    // println!("{:?}", cups);
-    // println!("Current: {}", current_cup);
+    trace!("cups: {:?}, current: {}", cups, current_cup);
 
     let mut group = vec![];
     let current_index = cups.iter().position(|x| x == &current_cup).unwrap();
     let max_label: u32 = cups.len().try_into().unwrap();
-    // println!("current_idx: {}", current_index);
     let mut idx = (current_index + 1) % cups.len();
     for _ in 0..3 {
         group.push(cups.remove(idx));
@@ -38,29 +71,24 @@ fn step(cups: &mut Vec<u32>, current_cup: u32) -> u32 {
         }
     }
 
-    //  println!("group: {:?}", group);
+    trace!("pick up: {:?}", group);
 
     // this is some modular hackery to avoid underflowing our unsigned ints
     // e.g. (x + 7) mod 9 + 1 is equivalent to (x + 8) mod 9 and thus also (x-1) mod 9  but
     // this way it in the range 1, 2, ..., 9
     let mut target_cup = (current_cup + (max_label - 2)) % max_label + 1;
-    // println!("trying target cup {}", target_cup);
     while group.contains(&target_cup) {
         target_cup = (target_cup + (max_label - 2)) % max_label + 1;
-        // println!("adjusted target cup to {}", target_cup);
     }
-    // println!("target_cup: {:?}", target_cup);
+    trace!("destination: {}", target_cup);
 
     let target_cup_idx = cups.iter().position(|x| x == &target_cup).unwrap();
-    // println!("target_cup_idx: {:?}", target_cup_idx);
 
     cups.insert(target_cup_idx + 1, group[0]);
     cups.insert(target_cup_idx + 2, group[1]);
     cups.insert(target_cup_idx + 3, group[2]);
 
     let current_index = cups.iter().position(|x| x == &current_cup).unwrap();
-    // println!("{:?}", cups);
-    // println!("Current: {}", current_cup);
     cups[(current_index + 1) % 9]
 }
 
@@ -71,34 +99,30 @@ fn step(cups: &mut Vec<u32>, current_cup: u32) -> u32 {
 /// We could make it a more general linked list by having the indices be arbitrary
 /// and the values be (next index, value), but we don't need that here!
 #[aoc(day23, part2)]
-pub fn part2(input: &str) -> usize {
-    #[allow(dead_code)]
-    const EXAMPLE: &str = "389125467";
-    // let input = EXAMPLE;
-
-    let base_cups: Vec<usize> = input
-        .chars()
-        .map(|x| x.to_digit(10).unwrap().try_into().unwrap())
-        .collect();
+pub fn part2(input: &[u32]) -> usize {
+    let base_cups: Vec<usize> = input.iter().map(|&x| x.try_into().unwrap()).collect();
     let mut cups = Cups::new(&base_cups, 1_000_000);
 
     for _ in 0..10_000_000 {
         cups.step();
     }
 
-    let after_one = cups.nexts[1];
-    let after_after_one = cups.nexts[after_one];
+    let after_one = cups.next(1);
+    let after_after_one = cups.next(after_one);
     after_after_one * after_one
 }
 
+/// The circle of cups as a linked list: `nexts[cup]` is the cup clockwise of `cup`.
+/// Index 0 is unused, since cups are labelled from 1.
 #[derive(Debug)]
-struct Cups {
+pub struct Cups {
     nexts: Vec<usize>,
     current_cup: usize,
 }
 
 impl Cups {
-    fn new(inital: &[usize], length: usize) -> Cups {
+    /// Starts with the cups in `inital`, followed by the rest of the cups up to `length` in order.
+    pub fn new(inital: &[usize], length: usize) -> Cups {
         let mut nexts = vec![0; length + 1];
         for i in 0..inital.len() - 1 {
             nexts[inital[i]] = inital[i + 1];
@@ -113,7 +137,17 @@ impl Cups {
         Cups { nexts, current_cup }
     }
 
-    fn step(&mut self) {
+    pub fn current_cup(&self) -> usize {
+        self.current_cup
+    }
+
+    /// The cup clockwise of `cup`.
+    pub fn next(&self, cup: usize) -> usize {
+        self.nexts[cup]
+    }
+
+    /// Makes one move.
+    pub fn step(&mut self) {
         let first_removed = self.nexts[self.current_cup];
         let second_removed = self.nexts[first_removed];
         let third_removed = self.nexts[second_removed];
@@ -135,12 +169,31 @@ impl Cups {
     }
 }
 
+pub struct Day23;
+
+impl Solution for Day23 {
+    type Input = Vec<u32>;
+    type Part1 = String;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     #[test]
     fn test_cup_new() {
-        const EXAMPLE: &str = "389125467";
         let cups: Vec<usize> = EXAMPLE
             .chars()
             .map(|x| x.to_digit(10).unwrap().try_into().unwrap())
@@ -7,33 +7,68 @@
 //! since we want to iterate not just over all coords in the map, but over also adjacent ones.
 use std::collections::HashMap;
 
-fn parse_line(input: &str) -> (i32, i32) {
-    let mut chars = input.chars();
+use crate::error::{ParseError, Source};
+use crate::example::Example;
+use crate::solution::Solution;
+
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: EXAMPLE,
+        expected: "10",
+    },
+    Example {
+        part: 2,
+        input: EXAMPLE,
+        expected: "2208",
+    },
+];
+
+/// Follows one line of directions from the reference tile, giving the coordinates it ends up at.
+/// On bad input, returns the rest of the line starting from the direction that couldn't be read.
+pub fn parse_line(input: &str) -> Result<(i32, i32), &str> {
+    let mut chars = input.char_indices();
     let mut coords = (0, 0);
-    while let Some(c) = chars.next() {
+    while let Some((i, c)) = chars.next() {
         match c {
             'e' => coords.0 += 1,
             'w' => coords.0 -= 1,
-            'n' => match chars.next().unwrap() {
-                'e' => coords.1 += 1,
-                'w' => {
+            'n' => match chars.next() {
+                Some((_, 'e')) => coords.1 += 1,
+                Some((_, 'w')) => {
                     coords.1 += 1;
                     coords.0 -= 1;
                 }
-                x => panic!("unknown character following 'n': {}", x),
+                _ => return Err(&input[i..]),
             },
-            's' => match chars.next().unwrap() {
-                'w' => coords.1 -= 1,
-                'e' => {
+            's' => match chars.next() {
+                Some((_, 'w')) => coords.1 -= 1,
+                Some((_, 'e')) => {
                     coords.1 -= 1;
                     coords.0 += 1;
                 }
-                x => panic!("unknown character following 's': {}", x),
+                _ => return Err(&input[i..]),
             },
-            x => panic!("unknown character {}", x),
+            _ => return Err(&input[i..]),
         }
     }
-    coords
+    Ok(coords)
+}
+
+#[aoc_generator(day24)]
+pub fn parse(input: &str) -> Result<Vec<(i32, i32)>, ParseError> {
+    let src = Source::new(24, input);
+    input
+        .lines()
+        .map(|line| {
+            parse_line(line).map_err(|rest| {
+                src.error(
+                    rest,
+                    "expected one of the directions e, se, sw, w, nw or ne",
+                )
+            })
+        })
+        .collect()
 }
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
@@ -52,12 +87,11 @@ impl Color {
 }
 
 #[aoc(day24, part1)]
-pub fn day24(input: &str) -> usize {
+pub fn day24(input: &[(i32, i32)]) -> usize {
     let mut tiles = HashMap::new();
     input
-        .lines()
-        .map(|line| parse_line(line))
-        .for_each(|coords| tiles.entry(coords).or_insert(Color::White).flip());
+        .iter()
+        .for_each(|&coords| tiles.entry(coords).or_insert(Color::White).flip());
     tiles.values().filter(|&x| x == &Color::Black).count()
 }
 
@@ -94,7 +128,6 @@ impl Bounds {
     }
 }
 
-#[allow(dead_code)]
 const EXAMPLE: &str = "sesenwnenenewseeswwswswwnenewsewsw
 neeenesenwnwwswnenewnwwsewnenwseswesw
 seswneswswsenwwnwse
@@ -117,22 +150,21 @@ neswnwewnwnwseenwseesewsenwsweewe
 wseweeenwnesenwwwswnew";
 
 #[aoc(day24, part2)]
-pub fn part2(input: &str) -> usize {
-    // let input = EXAMPLE;
+pub fn part2(input: &[(i32, i32)]) -> usize {
     let mut tiles = HashMap::new();
 
     let mut bounds = Bounds::new();
-    input
-        .lines()
-        .map(|line| parse_line(line))
-        .for_each(|coords| {
-            bounds.update(coords.0, coords.1);
-            tiles.entry(coords).or_insert(Color::White).flip()
-        });
+    input.iter().for_each(|&coords| {
+        bounds.update(coords.0, coords.1);
+        tiles.entry(coords).or_insert(Color::White).flip()
+    });
     let n_steps = 100;
     for _ in 0..n_steps {
-        // let black_count = tiles.values().filter(|&x| x == &Color::Black).count();
-        // println!("black count: {}; bounds: {:?}", black_count, bounds);
+        trace!(
+            "black count: {}; bounds: {:?}",
+            tiles.values().filter(|&x| x == &Color::Black).count(),
+            bounds
+        );
         let output = step(&tiles, &bounds);
         tiles = output.0;
         bounds = output.1;
@@ -190,20 +222,44 @@ fn black_neighbors(grid: &HashMap<(i32, i32), Color>, (x, y): (i32, i32)) -> i32
     ret
 }
 
+pub struct Day24;
+
+impl Solution for Day24 {
+    type Input = Vec<(i32, i32)>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        day24(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::parse_line;
     #[test]
     fn parse_hexagon() {
         // single instr
-        assert_eq!(parse_line("w"), (-1, 0));
-        assert_eq!(parse_line("e"), (1, 0));
-        assert_eq!(parse_line("sw"), (0, -1));
-        assert_eq!(parse_line("se"), (1, -1));
-        assert_eq!(parse_line("ne"), (0, 1));
-        assert_eq!(parse_line("nw"), (-1, 1));
+        assert_eq!(parse_line("w"), Ok((-1, 0)));
+        assert_eq!(parse_line("e"), Ok((1, 0)));
+        assert_eq!(parse_line("sw"), Ok((0, -1)));
+        assert_eq!(parse_line("se"), Ok((1, -1)));
+        assert_eq!(parse_line("ne"), Ok((0, 1)));
+        assert_eq!(parse_line("nw"), Ok((-1, 1)));
 
         // some more complex ones
-        assert_eq!(parse_line("nwwswee"), (0, 0));
+        assert_eq!(parse_line("nwwswee"), Ok((0, 0)));
+
+        // bad directions point at where things went wrong
+        assert_eq!(parse_line("eenxw"), Err("nxw"));
+        assert_eq!(parse_line("es"), Err("s"));
     }
 }
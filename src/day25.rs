@@ -1,16 +1,46 @@
-#[aoc(day25, part1)]
-pub fn part1(input: &str) -> i64 {
+use crate::error::{ParseError, Source};
+use crate::example::Example;
+use crate::solution::Solution;
+
+pub const EXAMPLES: &[Example] = &[Example {
+    part: 1,
+    input: "5764801\n17807724",
+    expected: "14897079",
+}];
+
+pub const MODULUS: i64 = 20201227;
+
+#[aoc_generator(day25)]
+pub fn parse(input: &str) -> Result<(i64, i64), ParseError> {
+    let src = Source::new(25, input);
     let mut lines = input.lines();
-    let door_public_key: i64 = lines.next().unwrap().parse().unwrap();
-    let card_public_key: i64 = lines.next().unwrap().parse().unwrap();
+    let mut key = || -> Result<i64, ParseError> {
+        let line = lines
+            .next()
+            .ok_or_else(|| src.eof("expected two public keys"))?;
+        let key = src.number(line)?;
+        // anything else would never turn up in brute_force_loop_size
+        if !(1..MODULUS).contains(&key) {
+            return Err(src.error(
+                line,
+                format!("public keys must be between 1 and {}", MODULUS - 1),
+            ));
+        }
+        Ok(key)
+    };
+    Ok((key()?, key()?))
+}
 
+#[aoc(day25, part1)]
+pub fn part1(&(door_public_key, card_public_key): &(i64, i64)) -> i64 {
     let subject: i64 = 7;
-    let modulus: i64 = 20201227;
+    let modulus: i64 = MODULUS;
     let card_loop_size = brute_force_loop_size(subject, modulus, card_public_key);
     encrypt(door_public_key, modulus, card_loop_size)
 }
 
-fn brute_force_loop_size(subject: i64, modulus: i64, public_key: i64) -> i64 {
+/// Finds the loop size that transforms `subject` into `public_key`.
+pub fn brute_force_loop_size(subject: i64, modulus: i64, public_key: i64) -> i64 {
     let mut value = 1;
     for i in 1.. {
         value *= subject;
@@ -22,7 +52,8 @@ fn brute_force_loop_size(subject: i64, modulus: i64, public_key: i64) -> i64 {
     unreachable!()
 }
 
-fn encrypt(subject: i64, modulus: i64, loop_size: i64) -> i64 {
+/// Transforms `subject` with the given loop size.
+pub fn encrypt(subject: i64, modulus: i64, loop_size: i64) -> i64 {
     let mut value = 1;
     for _ in 0..loop_size {
         value *= subject;
@@ -30,3 +61,25 @@ fn encrypt(subject: i64, modulus: i64, loop_size: i64) -> i64 {
     }
     value
 }
+
+pub struct Day25;
+
+impl Solution for Day25 {
+    type Input = (i64, i64);
+    type Part1 = i64;
+    type Part2 = i64;
+
+    const PARTS: &'static [u32] = &[1];
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(_: &Self::Input) -> Self::Part2 {
+        unreachable!("day 25 doesn't have a part 2")
+    }
+}
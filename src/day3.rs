@@ -1,4 +1,36 @@
-#[derive(Debug, Copy, Clone)]
+use std::collections::HashSet;
+
+use crate::error::{ParseError, Source};
+use crate::example::Example;
+use crate::grid::{Descent, WrappingGrid};
+use crate::solution::Solution;
+
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: EXAMPLE,
+        expected: "7",
+    },
+    Example {
+        part: 2,
+        input: EXAMPLE,
+        expected: "336",
+    },
+];
+
+const EXAMPLE: &str = "..##.......
+#...#...#..
+.#....#..#.
+..#.#...#.#
+.#...##..#.
+..#.##.....
+.#.#.#....#
+.#........#
+#.##...#...
+#...##....#
+.#..#...#.#";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Square {
     Open,
     Tree,
@@ -13,60 +45,75 @@ impl std::fmt::Display for Square {
     }
 }
 
-#[derive(Debug)]
-pub struct Grid {
-    width: usize,
-    height: usize,
-    grid: Vec<Vec<Square>>,
-}
+/// The map repeats to the right (and, for slopes going left, to the left).
+pub type Grid = WrappingGrid<Square>;
 
 #[aoc_generator(day3)]
-pub fn gen(input: &str) -> Grid {
+pub fn gen(input: &str) -> Result<Grid, ParseError> {
+    let src = Source::new(3, input);
     let mut ret: Vec<Vec<Square>> = vec![];
     for line in input.lines() {
-        ret.push(
-            line.chars()
-                .map(|x| match x {
-                    '.' => Square::Open,
-                    '#' => Square::Tree,
-                    _ => panic!("bad square"),
-                })
-                .collect(),
-        );
+        let row = line
+            .char_indices()
+            .map(|(i, x)| match x {
+                '.' => Ok(Square::Open),
+                '#' => Ok(Square::Tree),
+                _ => Err(src.error(&line[i..], format!("expected '.' or '#', found {:?}", x))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(first) = ret.first() {
+            if row.len() != first.len() {
+                return Err(src.error(
+                    line,
+                    format!(
+                        "row is {} squares wide, but the first row is {}",
+                        row.len(),
+                        first.len()
+                    ),
+                ));
+            }
+        }
+        ret.push(row);
     }
-    Grid {
-        width: ret[0].len(),
-        height: ret.len(),
-        grid: ret,
+    if ret.is_empty() || ret[0].is_empty() {
+        return Err(src.eof("the map is empty"));
     }
+    Ok(Grid::new(ret))
 }
 
 #[aoc(day3, part1)]
-pub fn solve_1(input: &Grid) -> i32 {
-    let mut ans = 0;
-    for (i, row) in input.grid.iter().enumerate() {
-        let col = (3 * i) % input.width;
-        let square = row[col];
-        if let Square::Tree = square {
-            ans += 1;
-        }
-    }
-    ans
+pub fn solve_1(input: &Grid) -> i64 {
+    one_slope(input, 3, 1)
+}
+
+/// Counts the trees hit going `dx` right and `dy` down at a time, from the top left to the
+/// bottom. Negative `dx` goes left, and negative `dy` goes up from the bottom left instead.
+pub fn one_slope(input: &Grid, dx: i64, dy: i64) -> i64 {
+    input.count(dx, dy, |&square| square == Square::Tree) as i64
 }
 
-fn one_slope(input: &Grid, dx: usize, dy: usize) -> i64 {
-    let mut ans = 0;
-    let mut row = 0;
-    let mut col = 0;
-    while row < input.grid.len() {
-        let square = input.grid[row][col];
-        if let Square::Tree = square {
-            ans += 1;
+/// Every slope up to `bound` in each direction, from the fewest trees hit to the most.
+pub fn rank_slopes(input: &Grid, bound: i64) -> Vec<Descent> {
+    input.rank_slopes(bound, |&square| square == Square::Tree)
+}
+
+/// The map with a path drawn on it the way the puzzle description does: O where it crosses an
+/// open square and X where it hits a tree.
+pub fn render(input: &Grid, path: &[(usize, usize)]) -> String {
+    let on_path: HashSet<_> = path.iter().copied().collect();
+    let mut out = String::new();
+    for row in 0..input.height() {
+        for col in 0..input.width() {
+            let square = *input.get(row as i64, col as i64).unwrap();
+            out.push(match (on_path.contains(&(row, col)), square) {
+                (true, Square::Open) => 'O',
+                (true, Square::Tree) => 'X',
+                (false, square) => square.to_string().chars().next().unwrap(),
+            });
         }
-        row += dy;
-        col = (col + dx) % input.width;
+        out.push('\n');
     }
-    ans
+    out
 }
 
 #[aoc(day3, part2)]
@@ -75,8 +122,52 @@ pub fn solve_2(input: &Grid) -> i64 {
     let mut ans = 1;
     for (dx, dy) in slopes {
         let trees = one_slope(input, dx, dy);
-        println!("for ({}, {}) got {}", dx, dy, trees);
+        debug!("for ({}, {}) got {}", dx, dy, trees);
         ans *= trees;
     }
     ans
 }
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    type Input = Grid;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        gen(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        solve_1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        solve_2(input)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_slopes() {
+        let grid = gen(EXAMPLE).unwrap();
+        assert_eq!(one_slope(&grid, 3, 1), 7);
+        // The map is 11 wide, so going 3 left lands on the same squares as going 8 right
+        assert_eq!(one_slope(&grid, -3, 1), one_slope(&grid, 8, 1));
+        let ranked = rank_slopes(&grid, 3);
+        assert_eq!(ranked.len(), 42);
+        assert!(ranked.windows(2).all(|w| w[0].hits <= w[1].hits));
+        let best = &ranked[0];
+        assert_eq!(best.hits as i64, one_slope(&grid, best.dx, best.dy));
+
+        let drawn = render(&grid, &grid.path(3, 1).collect::<Vec<_>>());
+        let lines: Vec<_> = drawn.lines().collect();
+        assert_eq!(lines[0], "O.##.......");
+        assert_eq!(lines[1], "#..O#...#..");
+        assert_eq!(lines[2], ".#....X..#.");
+    }
+}
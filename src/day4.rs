@@ -1,130 +1,128 @@
-use regex::Regex;
 use std::collections::HashMap;
 
-const KEYS: [&str; 8] = ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid", "cid"];
+use crate::error::ParseError;
+use crate::example::Example;
+use crate::passport::{self, Report, Schema};
+use crate::solution::Solution;
 
-struct Passport(HashMap<String, String>);
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
+byr:1937 iyr:2017 cid:147 hgt:183cm
+
+iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884
+hcl:#cfa07d byr:1929
+
+hcl:#ae17e1 iyr:2013
+eyr:2024
+ecl:brn pid:760753108 byr:1931
+hgt:179cm
+
+hcl:#cfa07d eyr:2025 pid:166559648
+iyr:2011 ecl:brn hgt:59in",
+        expected: "2",
+    },
+    // The first four are the invalid passports from the description, the last four the valid ones.
+    Example {
+        part: 2,
+        input: "eyr:1972 cid:100
+hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926
+
+iyr:2019
+hcl:#602927 eyr:1967 hgt:170cm
+ecl:grn pid:012533040 byr:1946
+
+hcl:dab227 iyr:2012
+ecl:brn hgt:182cm pid:021572410 eyr:2020 byr:1992 cid:277
+
+hgt:59cm ecl:zzz
+eyr:2038 hcl:74454a iyr:2023
+pid:3556412378 byr:2007
+
+pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980
+hcl:#623a2f
+
+eyr:2029 ecl:blu cid:129 byr:1989
+iyr:2014 pid:896056539 hcl:#a97842 hgt:165cm
+
+hcl:#888785
+hgt:164cm byr:2001 iyr:2015 cid:88
+pid:545766238 ecl:hzl
+eyr:2022
+
+iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719",
+        expected: "4",
+    },
+];
+
+/// A passport's fields, by key. Parsing doesn't check which fields there are, or their values;
+/// that's what the two parts are about, and `passport::Schema` does the checking.
+#[derive(Debug, Clone)]
+pub struct Passport(HashMap<String, String>);
 impl Passport {
-    fn from_str(s: &str) -> Option<Passport> {
-        let mut passport = HashMap::new();
-        for pair in s.split_whitespace() {
-            let pair: Vec<_> = pair.split(':').collect();
-            let key = pair[0];
-            let value = pair[1];
-            if !KEYS.contains(&key) {
-                return None;
-            }
-            passport.insert(key.to_owned(), value.to_owned());
-        }
-        let expected_length = if passport.get("cid").is_some() { 8 } else { 7 };
-        if passport.len() == expected_length {
-            Some(Passport(passport))
-        } else {
-            None
-        }
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
     }
 
-    fn from_str_with_validation(s: &str) -> Option<Passport> {
-        let mut passport = HashMap::new();
-        for pair in s.split_whitespace() {
-            let pair: Vec<_> = pair.split(':').collect();
-            let key = pair[0];
-            let value = pair[1];
-            if !KEYS.contains(&key) {
-                return None;
-            }
-            let valid = Passport::field_is_valid(key, value);
-            // DEBUG println!("{}:{} is {}", key, value, valid);
-            if !valid {
-                return None;
-            }
-            passport.insert(key.to_owned(), value.to_owned());
-        }
-        let expected_length = if passport.get("cid").is_some() { 8 } else { 7 };
-        if passport.len() == expected_length {
-            Some(Passport(passport))
-        } else {
-            None
-        }
+    /// Everything wrong with the passport, going by `schema`.
+    pub fn validate(&self, schema: &Schema) -> Report {
+        let report = schema.validate(&self.0);
+        trace!("{:?}: {}", self.0, report);
+        report
     }
+}
 
-    fn field_is_valid(key: &str, value: &str) -> bool {
-        match key {
-            "byr" => value
-                .parse::<u32>()
-                .map(|x| x >= 1920 && x <= 2002)
-                .unwrap_or(false),
-            "iyr" => value
-                .parse::<u32>()
-                .map(|x| x >= 2010 && x <= 2020)
-                .unwrap_or(false),
-            "eyr" => value
-                .parse::<u32>()
-                .map(|x| x >= 2020 && x <= 2030)
-                .unwrap_or(false),
-            "hgt" => {
-                let chars = value;
-                if chars.len() <= 2 {
-                    return false;
-                }
-                let head = &chars[..chars.len() - 2];
-                let tail = &chars[chars.len() - 2..];
-                let head = match head.to_string().parse::<i32>() {
-                    Ok(val) => val,
-                    Err(_) => return false,
-                };
-                match tail {
-                    "in" => head >= 59 && head <= 76,
-                    "cm" => head >= 150 && head <= 193,
-                    _ => false,
-                }
+/// Anything odd about a passport, like an unknown key, is logged and otherwise left for
+/// validation to catch, so one bad token doesn't lose the whole batch.
+#[aoc_generator(day4)]
+pub fn parse(input: &str) -> Result<Vec<Passport>, ParseError> {
+    let records = passport::parse_records(input, &Schema::puzzle());
+    Ok(records
+        .iter()
+        .map(|record| {
+            for warning in &record.warnings {
+                info!("{}", warning);
             }
-            "hcl" => {
-                let color_re = Regex::new(r"^#[0-9a-f]{6}$").unwrap();
-                color_re.is_match(value)
-            }
-            "ecl" => {
-                let ecl_re = Regex::new("^amb|blu|brn|gry|grn|hzl|oth$").unwrap();
-                ecl_re.is_match(value)
-            }
-            "pid" => {
-                let pid_re = Regex::new("^[0-9]{9}$").unwrap();
-                pid_re.is_match(value)
-            }
-            "cid" => true,
-            _ => false,
-        }
-    }
+            Passport(record.to_map())
+        })
+        .collect())
 }
 
-#[aoc_generator(day4, part1)]
-pub fn gen(input: &str) -> i32 {
-    let mut cnt = 0;
-    for chunk in input.split("\n\n") {
-        if Passport::from_str(chunk).is_some() {
-            cnt += 1;
-        }
-    }
-    cnt
+#[aoc(day4, part1)]
+pub fn solve_1(input: &[Passport]) -> usize {
+    let schema = Schema::puzzle();
+    input
+        .iter()
+        .filter(|p| p.validate(&schema).has_required_fields())
+        .count()
 }
 
-#[aoc(day4, part1)]
-pub fn solve_1(input: &i32) -> i32 {
-    *input
+#[aoc(day4, part2)]
+pub fn solve_2(input: &[Passport]) -> usize {
+    let schema = Schema::puzzle();
+    input
+        .iter()
+        .filter(|p| p.validate(&schema).is_valid())
+        .count()
 }
 
-#[aoc_generator(day4, part2)]
-pub fn gen2(input: &str) -> i32 {
-    let mut cnt = 0;
-    for chunk in input.split("\n\n") {
-        if Passport::from_str_with_validation(chunk).is_some() {
-            cnt += 1;
-        }
+pub struct Day4;
+
+impl Solution for Day4 {
+    type Input = Vec<Passport>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
     }
-    cnt
-}
 
-#[aoc(day4, part2)]
-pub fn solve_2(input: &i32) -> i32 {
-    *input
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        solve_1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        solve_2(input)
+    }
 }
@@ -1,14 +1,43 @@
 use std::convert::TryInto;
 
+use crate::error::{ParseError, Source};
+use crate::example::Example;
+use crate::solution::Solution;
+
+/// There's no example for part 2, since it needs a whole plane's worth of boarding passes.
+pub const EXAMPLES: &[Example] = &[Example {
+    part: 1,
+    input: "FBFBBFFRLR
+BFFFBBFRRR
+FFFBBBFRRR
+BBFFBBFRLL",
+    expected: "820",
+}];
+
 #[aoc_generator(day5)]
-pub fn pt1(input: &str) -> Vec<(i64, i64)> {
-    input.lines().map(one_line).collect()
+pub fn pt1(input: &str) -> Result<Vec<(i64, i64)>, ParseError> {
+    let src = Source::new(5, input);
+    input.lines().map(|line| one_line(&src, line)).collect()
 }
 
-fn one_line(line: &str) -> (i64, i64) {
+fn one_line(src: &Source, line: &str) -> Result<(i64, i64), ParseError> {
+    if line.len() != 10 {
+        return Err(src.error(
+            line,
+            format!("expected a 10-letter boarding pass, found {:?}", line),
+        ));
+    }
+    if let Some(i) = line.find(|c| !"FB".contains(c)) {
+        if i < 7 {
+            return Err(src.error(&line[i..], "the first 7 letters must be F or B"));
+        }
+    }
+    if let Some(i) = line[7..].find(|c| !"LR".contains(c)) {
+        return Err(src.error(&line[7 + i..], "the last 3 letters must be L or R"));
+    }
     let row = char_binary_search(&line[0..7], 'F', 'B');
     let col = char_binary_search(&line[7..10], 'L', 'R');
-    (row, col)
+    Ok((row, col))
 }
 
 fn char_binary_search(input: &str, left: char, right: char) -> i64 {
@@ -35,7 +64,7 @@ pub fn pt1_go(input: &[(i64, i64)]) -> i64 {
 pub fn pt2_go(input: &[(i64, i64)]) -> i64 {
     let mut ids: Vec<_> = input.iter().map(|(x, y)| 8 * x + y).collect::<Vec<_>>();
     ids.sort_unstable();
-    // println!("{:?}", ids);
+    trace!("{:?}", ids);
     for (i, id) in ids.iter().enumerate().skip(1) {
         if ids[i - 1] == id - 2 {
             return *id - 1;
@@ -43,3 +72,23 @@ pub fn pt2_go(input: &[(i64, i64)]) -> i64 {
     }
     unreachable!()
 }
+
+pub struct Day5;
+
+impl Solution for Day5 {
+    type Input = Vec<(i64, i64)>;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        pt1(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        pt1_go(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        pt2_go(input)
+    }
+}
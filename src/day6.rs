@@ -1,15 +1,60 @@
 use std::collections::HashSet;
 use std::convert::TryInto;
 
-type Group = Vec<HashSet<char>>;
+use crate::error::{ParseError, Source};
+use crate::example::Example;
+use crate::solution::Solution;
+
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: EXAMPLE,
+        expected: "11",
+    },
+    Example {
+        part: 2,
+        input: EXAMPLE,
+        expected: "6",
+    },
+];
+
+const EXAMPLE: &str = "abc
+
+a
+b
+c
+
+ab
+ac
+
+a
+a
+a
+a
+
+b";
+
+/// The answers from each person in one group.
+pub type Group = Vec<HashSet<char>>;
 
 #[aoc_generator(day6)]
-pub fn gen(input: &str) -> Vec<Group> {
+pub fn gen(input: &str) -> Result<Vec<Group>, ParseError> {
+    let src = Source::new(6, input);
     let mut groups = vec![];
     for group in input.split("\n\n") {
-        groups.push(group.lines().map(|line| line.chars().collect()).collect());
+        let mut people = vec![];
+        for line in group.lines() {
+            if let Some(i) = line.find(|c: char| !c.is_ascii_lowercase()) {
+                return Err(src.error(&line[i..], "answers must be letters from a to z"));
+            }
+            if line.is_empty() {
+                return Err(src.error(line, "found an empty line inside a group"));
+            }
+            people.push(line.chars().collect());
+        }
+        groups.push(people);
     }
-    groups
+    Ok(groups)
 }
 
 #[aoc(day6, part1)]
@@ -37,3 +82,23 @@ pub fn part2(groups: &[Group]) -> i64 {
     }
     ans.try_into().unwrap()
 }
+
+pub struct Day6;
+
+impl Solution for Day6 {
+    type Input = Vec<Group>;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        gen(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        part2(input)
+    }
+}
@@ -3,11 +3,65 @@ use std::collections::HashMap;
 use std::collections::HashSet;
 use std::rc::Rc;
 
+use crate::error::{ParseError, Source};
+use crate::example::Example;
+use crate::solution::Solution;
+
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: EXAMPLE,
+        expected: "4",
+    },
+    Example {
+        part: 2,
+        input: EXAMPLE,
+        expected: "32",
+    },
+    Example {
+        part: 2,
+        input: "shiny gold bags contain 2 dark red bags.
+dark red bags contain 2 dark orange bags.
+dark orange bags contain 2 dark yellow bags.
+dark yellow bags contain 2 dark green bags.
+dark green bags contain 2 dark blue bags.
+dark blue bags contain 2 dark violet bags.
+dark violet bags contain no other bags.",
+        expected: "126",
+    },
+];
+
+const EXAMPLE: &str = "light red bags contain 1 bright white bag, 2 muted yellow bags.
+dark orange bags contain 3 bright white bags, 4 muted yellow bags.
+bright white bags contain 1 shiny gold bag.
+muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.
+shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.
+dark olive bags contain 3 faded blue bags, 4 dotted black bags.
+vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
+faded blue bags contain no other bags.
+dotted black bags contain no other bags.";
+
+/// For each color of bag, how many of each other color it has to contain.
+pub type Rules = HashMap<String, Vec<(usize, String)>>;
+
 #[aoc_generator(day7)]
-pub fn gen(input: &str) -> HashMap<String, Vec<(usize, String)>> {
+pub fn gen(input: &str) -> Result<Rules, ParseError> {
+    let src = Source::new(7, input);
     let mut rules = HashMap::new();
     for line in input.lines() {
         let words: Vec<_> = line.split(' ').collect();
+        // every rule is "<adj> <color> bags contain " followed by either "no other bags."
+        // or some number of "<n> <adj> <color> bag(s)," groups of four words
+        let well_formed = words.len() >= 7
+            && words[2] == "bags"
+            && words[3] == "contain"
+            && (words[4] == "no" || (words.len() - 4) % 4 == 0);
+        if !well_formed {
+            return Err(src.error(
+                line,
+                format!("expected a rule like \"<color> bags contain <n> <color> bags, ...\", found {:?}", line),
+            ));
+        }
         let source = words[..2].join(" ");
         if words[4] == "no" {
             rules.insert(source, vec![]);
@@ -17,17 +71,17 @@ pub fn gen(input: &str) -> HashMap<String, Vec<(usize, String)>> {
         let mut targets = Vec::with_capacity(n_contains);
         for target_num in 0..n_contains {
             let start = target_num * 4 + 4;
-            let count: usize = words[start].parse().unwrap();
+            let count: usize = src.number(words[start])?;
             let target = words[start + 1..start + 3].join(" ");
             targets.push((count, target));
         }
         rules.insert(source, targets);
     }
-    rules
+    Ok(rules)
 }
 
 #[aoc(day7, part1)]
-pub fn day1(input: &HashMap<String, Vec<(usize, String)>>) -> usize {
+pub fn day1(input: &Rules) -> usize {
     let mut reverse_direct_inclusions = HashMap::new();
     for (bag, contains) in input {
         for (_, containee) in contains {
@@ -52,16 +106,19 @@ pub fn day1(input: &HashMap<String, Vec<(usize, String)>>) -> usize {
 }
 
 #[aoc(day7, part2)]
-pub fn day2(input: &HashMap<String, Vec<(usize, String)>>) -> usize {
+pub fn day2(input: &Rules) -> usize {
+    bags_inside("shiny gold", input)
+}
+
+/// How many bags a bag of the given color has to contain, all the way down.
+pub fn bags_inside(color: &str, rules: &Rules) -> usize {
     let cache = Rc::new(RefCell::new(HashMap::new()));
-    day2_helper("shiny gold", input, cache)
+    // the helper counts the bag it's given as well as the ones inside it,
+    // but the question only asks about the bags inside it
+    day2_helper(color, rules, cache) - 1
 }
 
-fn day2_helper(
-    target: &str,
-    input: &HashMap<String, Vec<(usize, String)>>,
-    cache: Rc<RefCell<HashMap<String, usize>>>,
-) -> usize {
+fn day2_helper(target: &str, input: &Rules, cache: Rc<RefCell<HashMap<String, usize>>>) -> usize {
     if let Some(val) = cache.borrow().get(target) {
         return *val;
     }
@@ -72,6 +129,26 @@ fn day2_helper(
         acc += child.0 * day2_helper(&child.1, input, cache.clone());
     }
     cache.borrow_mut().insert(target.to_owned(), acc);
-    println!("helper for {} got {}", target, acc);
+    trace!("helper for {} got {}", target, acc);
     acc
 }
+
+pub struct Day7;
+
+impl Solution for Day7 {
+    type Input = Rules;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        gen(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        day1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        day2(input)
+    }
+}
@@ -1,113 +1,103 @@
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
-pub enum Instruction {
-    Jmp(i32),
-    Nop(i32),
-    Acc(i32),
-}
+//! The console itself lives in `console`; this is just the puzzle's use of it.
+pub use crate::console::{ControlFlow, Flip, Halt, Instruction, Machine};
 
-use std::collections::HashSet;
-use std::convert::TryFrom;
+use crate::console::InstructionSet;
+use crate::error::ParseError;
+use crate::example::Example;
+use crate::solution::Solution;
 
-use Instruction::*;
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: EXAMPLE,
+        expected: "5",
+    },
+    Example {
+        part: 2,
+        input: EXAMPLE,
+        expected: "8",
+    },
+];
 
-impl Instruction {
-    fn from_str(input: &str) -> Option<Instruction> {
-        if input.len() < 6 {
-            return None;
-        }
-        let body = input[4..].parse();
-        let body = match body {
-            Ok(v) => v,
-            Err(_) => return None,
-        };
-        match &input[..3] {
-            "jmp" => Some(Instruction::Jmp(body)),
-            "acc" => Some(Instruction::Acc(body)),
-            "nop" => Some(Instruction::Nop(body)),
-            _ => None,
-        }
-    }
-}
+pub(crate) const EXAMPLE: &str = "nop +0
+acc +1
+jmp +4
+acc +3
+jmp -3
+acc -99
+acc +1
+jmp -4
+acc +6";
 
-pub struct Execution {
-    program: Vec<Instruction>,
-    pc: i32,
-    acc: i32,
+#[aoc_generator(day8)]
+pub fn parse(input: &str) -> Result<Vec<Instruction>, ParseError> {
+    InstructionSet::new()
+        .parse(input)
+        .map_err(|e| e.into_parse_error(8))
 }
 
-impl Execution {
-    fn step(&mut self) {
-        let instr = self.program[usize::try_from(self.pc).unwrap()];
-        match instr {
-            Jmp(arg) => self.pc += arg,
-            Acc(arg) => {
-                self.pc += 1;
-                self.acc += arg;
-            }
-            Nop(_) => self.pc += 1,
+/// `None` if the program halts some other way than looping.
+#[aoc(day8, part1)]
+pub fn day1(input: &[Instruction]) -> Option<i32> {
+    let mut machine = Machine::new(input.to_owned());
+    match machine.run_until_repeat() {
+        Halt::Loop { .. } => Some(machine.acc()),
+        halt => {
+            debug!(
+                "expected the program to loop, but it halted with {:?}",
+                halt
+            );
+            None
         }
     }
 }
 
-#[aoc_generator(day8)]
-pub fn parse(input: &str) -> Vec<Instruction> {
-    input
-        .lines()
-        .map(|x| Instruction::from_str(x).unwrap())
-        .collect()
+/// The first version of this tried flipping every `jmp` and `nop` in turn and reran the whole
+/// program each time. `ControlFlow` finds the flip in linear time instead.
+/// `None` if no single flip makes the program terminate.
+#[aoc(day8, part2)]
+pub fn day2(input: &[Instruction]) -> Option<i32> {
+    let flip = ControlFlow::new(input).repair()?;
+    debug!(
+        "flipping {:?} at pc {} to {:?}",
+        flip.from, flip.pc, flip.to
+    );
+    let mut machine = Machine::new(flip.apply(input));
+    assert!(machine.run().is_terminated());
+    Some(machine.acc())
 }
 
-#[aoc(day8, part1)]
-pub fn day1(input: &[Instruction]) -> i32 {
-    let mut execution = Execution {
-        acc: 0,
-        pc: 0,
-        program: input.to_owned(),
-    };
-    execution.run_to_completion().unwrap_err()
-}
+pub struct Day8;
 
-impl Execution {
-    pub fn run_to_completion(&mut self) -> Result<i32, i32> {
-        let mut visited_pcs: HashSet<i32> = HashSet::new();
-        loop {
-            if visited_pcs.contains(&self.pc) {
-                return Err(self.acc);
-            } else if self.pc == i32::try_from(self.program.len()).unwrap() {
-                return Ok(self.acc);
-            }
-            visited_pcs.insert(self.pc);
-            self.step()
-        }
+impl Solution for Day8 {
+    type Input = Vec<Instruction>;
+    type Part1 = Option<i32>;
+    type Part2 = Option<i32>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        day1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        day2(input)
     }
 }
-#[aoc(day8, part2)]
-pub fn day2(input: &[Instruction]) -> i32 {
-    for (i, instr) in input.iter().enumerate() {
-        let mut exec = match instr {
-            Jmp(v) => {
-                let mut program = input.to_owned();
-                program[i] = Nop(*v);
-                Execution {
-                    program,
-                    pc: 0,
-                    acc: 0,
-                }
-            }
-            Nop(v) => {
-                let mut program = input.to_owned();
-                program[i] = Jmp(*v);
-                Execution {
-                    program,
-                    pc: 0,
-                    acc: 0,
-                }
-            }
-            _ => continue,
-        };
-        if let Ok(ans) = exec.run_to_completion() {
-            return ans;
-        }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_no_answer() {
+        let program = parse(EXAMPLE).unwrap();
+        assert_eq!((day1(&program), day2(&program)), (Some(5), Some(8)));
+        // Terminates straight away, so there's no loop
+        assert_eq!(day1(&parse("acc +1").unwrap()), None);
+        // Loops whichever instruction is flipped
+        assert_eq!(day2(&parse("jmp +0\njmp -1").unwrap()), None);
     }
-    panic!()
 }
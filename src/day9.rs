@@ -1,77 +1,383 @@
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 
+use crate::error::{ParseError, Source};
+use crate::example::Example;
+use crate::solution::Solution;
+
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: EXAMPLE,
+        expected: "127",
+    },
+    Example {
+        part: 2,
+        input: EXAMPLE,
+        expected: "62",
+    },
+];
+
+/// The example's preamble is only 5 numbers long, so the examples are run by `Day9Example`.
+const EXAMPLE: &str = "35
+20
+15
+25
+47
+40
+62
+55
+65
+95
+102
+117
+150
+182
+127
+219
+299
+277
+309
+576";
+
+/// The preamble length the real inputs use.
+pub const PREAMBLE: usize = 25;
+
+/// The preamble length the example uses.
+pub const EXAMPLE_PREAMBLE: usize = 5;
+
+/// The XMAS-encrypted numbers, and how long their preamble is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Xmas {
+    pub preamble: usize,
+    pub numbers: Vec<i64>,
+}
+
+/// One number per line, with the real inputs' 25 number preamble.
 #[aoc_generator(day9)]
-pub fn gen(input: &str) -> Vec<i64> {
-    input.lines().map(|x| x.parse().unwrap()).collect()
+pub fn gen(input: &str) -> Result<Xmas, ParseError> {
+    gen_with_preamble(input, PREAMBLE)
 }
 
-#[aoc(day9, part1)]
-pub fn day1(input: &[i64]) -> i64 {
-    let mut buf: VecDeque<(i64, VecDeque<i64>)> = VecDeque::new();
-    for i in 0..25 {
-        let mut row = VecDeque::new();
-        for j in (i + 1)..25 {
-            row.push_back(input[i] + input[j]);
+pub fn gen_with_preamble(input: &str, preamble: usize) -> Result<Xmas, ParseError> {
+    let src = Source::new(9, input);
+    let numbers: Vec<i64> = input
+        .lines()
+        .map(|x| src.number(x))
+        .collect::<Result<_, _>>()?;
+    if numbers.len() <= preamble {
+        return Err(src.eof(format!(
+            "expected more than the {} numbers of the preamble, found {}",
+            preamble,
+            numbers.len()
+        )));
+    }
+    Ok(Xmas { preamble, numbers })
+}
+
+/// A number that isn't the sum of two of the numbers before it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Invalid {
+    /// Where the number is in the whole sequence, counting the preamble.
+    pub index: usize,
+    pub value: i64,
+}
+
+/// Checks XMAS numbers one at a time, against the `preamble` numbers before each.
+///
+/// This used to keep every pairwise sum of the window around, and search through all of them
+/// for each new number. Instead we keep a count of each value in the window, so checking a
+/// number is one lookup per number in the window, and sliding the window along is O(1).
+#[derive(Debug, Clone)]
+pub struct XmasValidator {
+    preamble: usize,
+    window: VecDeque<i64>,
+    counts: HashMap<i64, usize>,
+    index: usize,
+}
+
+impl XmasValidator {
+    pub fn new(preamble: usize) -> Self {
+        XmasValidator {
+            preamble,
+            window: VecDeque::with_capacity(preamble + 1),
+            counts: HashMap::new(),
+            index: 0,
         }
-        println!("{:?}", row);
-        buf.push_back((input[i], row));
     }
-    for val in &input[25..] {
-        // search for val in previous
-        if !search(&buf, val) {
-            return *val;
+
+    /// Whether two numbers at different positions in the window add up to `value`.
+    fn is_sum(&self, value: i64) -> bool {
+        self.window.iter().any(|&x| {
+            // If the other number would be out of range, it can't be in the window
+            let other = match value.checked_sub(x) {
+                Some(other) => other,
+                None => return false,
+            };
+            let needed = if x == other { 2 } else { 1 };
+            self.counts.get(&other).copied().unwrap_or(0) >= needed
+        })
+    }
+
+    /// Takes the next number, giving it back as `Invalid` if it's not the sum of two of the
+    /// previous `preamble` numbers. The preamble itself is always valid. Invalid numbers
+    /// still join the window, just like valid ones.
+    pub fn push(&mut self, value: i64) -> Result<(), Invalid> {
+        let valid = self.window.len() < self.preamble || self.is_sum(value);
+        self.window.push_back(value);
+        *self.counts.entry(value).or_insert(0) += 1;
+        if self.window.len() > self.preamble {
+            let old = self.window.pop_front().unwrap();
+            *self.counts.get_mut(&old).unwrap() -= 1;
         }
-        buf.pop_front();
-        for (old, row) in &mut buf {
-            row.push_back(val + *old);
+        let index = self.index;
+        self.index += 1;
+        if valid {
+            Ok(())
+        } else {
+            trace!("{} at {} isn't a sum of {:?}", value, index, self.window);
+            Err(Invalid { index, value })
         }
-        buf.push_back((*val, VecDeque::new()));
     }
-    0
 }
 
-fn search(buf: &VecDeque<(i64, VecDeque<i64>)>, val: &i64) -> bool {
-    for (_, row) in buf {
-        for v in row {
-            if *v == *val {
-                return true;
-            }
+/// Every invalid number in `input`, in order.
+pub fn invalid_numbers(input: &Xmas) -> Vec<Invalid> {
+    let mut validator = XmasValidator::new(input.preamble);
+    input
+        .numbers
+        .iter()
+        .filter_map(|&n| validator.push(n).err())
+        .collect()
+}
+
+/// `None` if every number is valid.
+#[aoc(day9, part1)]
+pub fn day1(input: &Xmas) -> Option<i64> {
+    let mut validator = XmasValidator::new(input.preamble);
+    let invalid = input
+        .numbers
+        .iter()
+        .find_map(|&n| validator.push(n).err())?;
+    Some(invalid.value)
+}
+
+/// A contiguous range of at least two numbers that adds up to the target.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Weakness {
+    pub start: usize,
+    /// Exclusive.
+    pub end: usize,
+    pub min: i64,
+    pub max: i64,
+}
+
+impl Weakness {
+    fn new(numbers: &[i64], start: usize, end: usize) -> Self {
+        let range = &numbers[start..end];
+        Weakness {
+            start,
+            end,
+            min: *range.iter().min().unwrap(),
+            max: *range.iter().max().unwrap(),
         }
     }
-    false
+
+    /// The puzzle's answer: the smallest and largest numbers in the range, added together.
+    pub fn answer(&self) -> i64 {
+        self.min + self.max
+    }
 }
 
-/// O(n^2) alg: make one pass through input, computing all possible subsequence sums as we go
-/// We maintain a list of all "live" sums, i.e. all sums that include the most recent element we've processed
-/// to update this list, we add the next value to every element in it, plus adding the current elemetn
-/// E.G for input [10, 9, 8, 7], we start with empty {}
-/// Then we process 10 by adding 10 to every element (but there are none), plus inserting 10, so we get {10}
-/// then we add 9 to each element, plus insert 9: {19, 9}
-/// an so on. At step n, the list has n elements, so 1 + 2 + ... + n = O(n^2)
-/// A slight modification is needed to track the min and max element appearng, but only slight.
-#[aoc(day9, part2)]
-pub fn day2(input: &[i64]) -> i64 {
-    let day1_answer = day1(input);
-
-    // (sum, (min in the sequence, max in the sequence))
-    let mut live_sums: Vec<(i64, (i64, i64))> = vec![];
-    let mut count = 0;
-    for &next in input {
-        let mut new_live_sums = Vec::with_capacity(live_sums.len());
-        for (k, (min, max)) in live_sums {
-            let new_min = min.min(next);
-            let new_max = max.max(next);
-            if next + k == day1_answer {
-                return new_min + new_max;
-            }
-            new_live_sums.push((next + k, (new_min, new_max)));
+/// The first range (the one that ends soonest, and then the longest of those) adding up to
+/// `target`.
+pub fn find_weakness(numbers: &[i64], target: i64) -> Option<Weakness> {
+    // Both searches find ranges in that order, so this only does as much work as it needs to.
+    weaknesses(numbers, target).next()
+}
+
+/// Every range adding up to `target`, ordered by where they end and then where they start.
+pub fn all_weaknesses(numbers: &[i64], target: i64) -> Vec<Weakness> {
+    weaknesses(numbers, target).collect()
+}
+
+fn weaknesses<'a>(numbers: &'a [i64], target: i64) -> Box<dyn Iterator<Item = Weakness> + 'a> {
+    let ranges: Box<dyn Iterator<Item = (usize, usize)>> = if numbers.iter().all(|&n| n >= 0) {
+        Box::new(window_ranges(numbers, target))
+    } else {
+        Box::new(prefix_sum_ranges(numbers, target))
+    };
+    Box::new(
+        ranges
+            .filter(|(start, end)| end - start >= 2)
+            .map(move |(start, end)| Weakness::new(numbers, start, end)),
+    )
+}
+
+/// With no negative numbers, a window's sum only goes up as its end moves right, and only
+/// goes down as its start does, so we can slide both along in one pass.
+fn window_ranges(numbers: &[i64], target: i64) -> impl Iterator<Item = (usize, usize)> + '_ {
+    let mut start = 0;
+    let mut sum = 0;
+    (1..=numbers.len()).flat_map(move |end| {
+        sum += numbers[end - 1];
+        while sum > target && start < end {
+            sum -= numbers[start];
+            start += 1;
         }
-        new_live_sums.push((next, (next, next)));
-        live_sums = new_live_sums;
-        if count <= 10 {
-            println!("{:?}", live_sums);
-            count += 1;
+        // Zeros at the start of the window can be dropped without changing the sum
+        let zeros = numbers[start..end].iter().take_while(|&&n| n == 0).count();
+        let first = start;
+        let found = sum == target && start < end;
+        (first..=first + zeros)
+            .filter(move |_| found)
+            .map(move |s| (s, end))
+    })
+}
+
+/// The general case: a range's sum is the difference of two prefix sums, so for each end we
+/// look up the starts whose prefix sum is exactly `target` less.
+fn prefix_sum_ranges(numbers: &[i64], target: i64) -> impl Iterator<Item = (usize, usize)> + '_ {
+    let mut starts: HashMap<i64, Vec<usize>> = HashMap::new();
+    starts.insert(0, vec![0]);
+    let mut sum = 0;
+    (1..=numbers.len()).flat_map(move |end| {
+        sum += numbers[end - 1];
+        let found: Vec<_> = starts
+            .get(&(sum - target))
+            .map(|s| s.iter().map(|&start| (start, end)).collect())
+            .unwrap_or_default();
+        starts.entry(sum).or_default().push(end);
+        found
+    })
+}
+
+/// This used to keep every "live" sum (every sum ending at the latest number) as it went,
+/// which is O(n^2). `find_weakness` is linear.
+/// `None` if there's no invalid number, or no range adds up to it.
+#[aoc(day9, part2)]
+pub fn day2(input: &Xmas) -> Option<i64> {
+    let target = day1(input)?;
+    let weakness = find_weakness(&input.numbers, target)?;
+    debug!(
+        "numbers {}..{} add up to {}",
+        weakness.start, weakness.end, target
+    );
+    Some(weakness.answer())
+}
+
+pub struct Day9;
+
+impl Solution for Day9 {
+    type Input = Xmas;
+    type Part1 = Option<i64>;
+    type Part2 = Option<i64>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        gen(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        day1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        day2(input)
+    }
+}
+
+/// Day 9 with the example's shorter preamble. It's registered as `solution::DAY9_EXAMPLE`,
+/// for running `EXAMPLES`, rather than in `DAYS`.
+pub struct Day9Example;
+
+impl Solution for Day9Example {
+    type Input = Xmas;
+    type Part1 = Option<i64>;
+    type Part2 = Option<i64>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        gen_with_preamble(input, EXAMPLE_PREAMBLE)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Part1 {
+        day1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Part2 {
+        day2(input)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_invalid_numbers() {
+        let input = gen_with_preamble(EXAMPLE, EXAMPLE_PREAMBLE).unwrap();
+        assert_eq!(input.preamble, 5);
+        // Too short for the real preamble
+        assert!(gen(EXAMPLE).is_err());
+        assert_eq!(
+            invalid_numbers(&input),
+            vec![Invalid {
+                index: 14,
+                value: 127
+            }]
+        );
+        // 10 can't be made from 5 + 5 unless there are two 5s
+        let mut validator = XmasValidator::new(3);
+        for n in &[5, 1, 2] {
+            validator.push(*n).unwrap();
         }
+        assert_eq!(
+            validator.push(10),
+            Err(Invalid {
+                index: 3,
+                value: 10
+            })
+        );
+        assert_eq!(validator.push(3), Ok(()));
+        validator.push(5).unwrap();
+        validator.push(5).unwrap_err();
+        assert_eq!(validator.push(10), Ok(()));
+
+        // Numbers so far apart that their difference doesn't fit in an i64
+        let mut validator = XmasValidator::new(2);
+        validator.push(i64::MIN).unwrap();
+        validator.push(i64::MAX).unwrap();
+        assert_eq!(validator.push(-1), Ok(()));
+        assert!(validator.push(i64::MIN).is_err());
+    }
+
+    #[test]
+    fn test_no_answer() {
+        let all_valid = gen_with_preamble("1\n2\n3\n5", 2).unwrap();
+        assert_eq!((day1(&all_valid), day2(&all_valid)), (None, None));
+        // 20 isn't a sum of two before it, but no range adds up to it either
+        let no_range = gen_with_preamble("1\n2\n3\n20", 2).unwrap();
+        assert_eq!((day1(&no_range), day2(&no_range)), (Some(20), None));
+    }
+
+    #[test]
+    fn test_weaknesses() {
+        let input = gen_with_preamble(EXAMPLE, EXAMPLE_PREAMBLE).unwrap();
+        let weakness = find_weakness(&input.numbers, 127).unwrap();
+        assert_eq!(
+            (weakness.start, weakness.end, weakness.answer()),
+            (2, 6, 62)
+        );
+
+        let ranges = |numbers: &[i64]| -> Vec<_> {
+            all_weaknesses(numbers, 3)
+                .iter()
+                .map(|w| (w.start, w.end))
+                .collect()
+        };
+        assert_eq!(
+            ranges(&[1, 2, 0, 3, 3, 0, 0]),
+            vec![(0, 2), (0, 3), (2, 4), (4, 6), (4, 7)]
+        );
+        // A negative number switches to the prefix sum search
+        assert_eq!(ranges(&[4, -1, 1, 2, 5, -2]), vec![(0, 2), (2, 4), (4, 6)]);
     }
-    unreachable!()
 }
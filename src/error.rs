@@ -0,0 +1,147 @@
+//! The error every day's parser returns when it can't make sense of its input.
+//!
+//! Parsers here mostly work by slicing up the input (`lines`, `split`, `&line[4..]` and so on),
+//! so rather than threading line numbers through every parser by hand, a `Source` wraps the
+//! whole input and works out the line and column of any slice of it when building an error.
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ParseError {
+    pub day: u32,
+    /// 1-based. A line of 0 means the error isn't about any one line.
+    pub line: usize,
+    /// 1-based, counted in characters.
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(day: u32, line: usize, column: usize, message: impl Into<String>) -> Self {
+        ParseError {
+            day,
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "day {} input: {}", self.day, self.message)
+        } else {
+            write!(
+                f,
+                "day {} input, line {}, column {}: {}",
+                self.day, self.line, self.column, self.message
+            )
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// The full input for one day, used to build `ParseError`s that point at the right place.
+#[derive(Debug, Copy, Clone)]
+pub struct Source<'a> {
+    pub day: u32,
+    pub input: &'a str,
+}
+
+impl<'a> Source<'a> {
+    pub fn new(day: u32, input: &'a str) -> Self {
+        Source { day, input }
+    }
+
+    /// An error pointing at the start of `fragment`, which should be a slice of the input.
+    /// If it isn't (e.g. it's been copied into a `String` along the way), the error
+    /// won't have a position.
+    pub fn error(&self, fragment: &str, message: impl Into<String>) -> ParseError {
+        let start = self.input.as_ptr() as usize;
+        let at = fragment.as_ptr() as usize;
+        if at < start || at > start + self.input.len() {
+            return self.error_without_position(message);
+        }
+        let before = &self.input[..at - start];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let column = before[line_start..].chars().count() + 1;
+        ParseError::new(self.day, line, column, self.with_hint(message.into()))
+    }
+
+    /// An error for input that stops before the parser has everything it needs.
+    pub fn eof(&self, message: impl Into<String>) -> ParseError {
+        let end = &self.input[self.input.len()..];
+        let message = format!("unexpected end of input: {}", message.into());
+        self.error(end, message)
+    }
+
+    pub fn error_without_position(&self, message: impl Into<String>) -> ParseError {
+        ParseError::new(self.day, 0, 0, self.with_hint(message.into()))
+    }
+
+    /// Parses `fragment` (a slice of the input) as a number.
+    pub fn number<T: FromStr>(&self, fragment: &str) -> Result<T, ParseError> {
+        fragment
+            .parse()
+            .map_err(|_| self.error(fragment, format!("expected a number, found {:?}", fragment)))
+    }
+
+    /// Splits `fragment` (usually a whole line) on `sep` and parses each piece as a number.
+    pub fn numbers<T: FromStr>(&self, fragment: &'a str, sep: char) -> Result<Vec<T>, ParseError> {
+        fragment.split(sep).map(|x| self.number(x)).collect()
+    }
+
+    /// Almost every parse failure on an input with Windows line endings is down to the stray
+    /// '\r's, so we point that out rather than leave people staring at an invisible character.
+    fn with_hint(&self, message: String) -> String {
+        if self.input.contains('\r') {
+            format!(
+                "{} (the input has CRLF line endings; convert it to LF)",
+                message
+            )
+        } else {
+            message
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_position() {
+        let input = "nop +0\nacc +1\njpm +4";
+        let src = Source::new(8, input);
+        let last = input.lines().nth(2).unwrap();
+        assert_eq!(
+            src.error(&last[4..], "bad"),
+            ParseError::new(8, 3, 5, "bad")
+        );
+        assert_eq!(src.eof("no more").line, 3);
+        let elsewhere = String::from("not from the input");
+        assert_eq!(
+            src.error(&elsewhere, "bad"),
+            ParseError::new(8, 0, 0, "bad")
+        );
+    }
+
+    #[test]
+    fn test_crlf() {
+        let input = "12\r\n34\r";
+        let src = Source::new(1, input);
+        let err = src
+            .number::<i32>(input.split('\n').nth(1).unwrap())
+            .unwrap_err();
+        assert_eq!(err.line, 2);
+        assert_eq!(
+            err.to_string(),
+            "day 1 input, line 2, column 1: expected a number, found \"34\\r\" \
+             (the input has CRLF line endings; convert it to LF)"
+        );
+    }
+}
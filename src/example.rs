@@ -0,0 +1,55 @@
+//! The example inputs from the puzzle descriptions, along with the answers the descriptions give.
+//! Each day keeps its own examples in an `EXAMPLES` constant next to its solvers; this module
+//! just collects them so the runner can check every day against them.
+use crate::solution::{self, Day};
+use crate::*;
+
+/// One example input and the published answer for one part.
+/// Some days use a different example for each part, and some parts have more than one example.
+#[derive(Debug)]
+pub struct Example {
+    pub part: u32,
+    pub input: &'static str,
+    pub expected: &'static str,
+}
+
+/// All of the registered examples for the given day, or none for a day that doesn't have any.
+pub fn examples(day: u32) -> &'static [Example] {
+    match day {
+        1 => day1::EXAMPLES,
+        2 => day2::EXAMPLES,
+        3 => day3::EXAMPLES,
+        4 => day4::EXAMPLES,
+        5 => day5::EXAMPLES,
+        6 => day6::EXAMPLES,
+        7 => day7::EXAMPLES,
+        8 => day8::EXAMPLES,
+        9 => day9::EXAMPLES,
+        10 => day10::EXAMPLES,
+        11 => day11::EXAMPLES,
+        12 => day12::EXAMPLES,
+        13 => day13::EXAMPLES,
+        14 => day14::EXAMPLES,
+        15 => day15::EXAMPLES,
+        16 => day16::EXAMPLES,
+        17 => day17::EXAMPLES,
+        18 => day18::EXAMPLES,
+        19 => day19::EXAMPLES,
+        20 => day20::EXAMPLES,
+        21 => day21::EXAMPLES,
+        22 => day22::EXAMPLES,
+        23 => day23::EXAMPLES,
+        24 => day24::EXAMPLES,
+        25 => day25::EXAMPLES,
+        _ => &[],
+    }
+}
+
+/// The solution the given day's examples are run with. That's the usual one, apart from day 9,
+/// whose example has a shorter preamble than the real inputs.
+pub fn solution(day: u32) -> Option<&'static Day> {
+    match day {
+        9 => Some(&solution::DAY9_EXAMPLE),
+        _ => solution::day(day),
+    }
+}
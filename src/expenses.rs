@@ -0,0 +1,150 @@
+//! Finding entries in an expense report that add up to a target, from day 1, for any number of
+//! entries and any target.
+//!
+//! This is meet in the middle: to pick k entries, every way of picking the last k - k/2 of them
+//! is summed up front and sorted, and then for each way of picking the first k/2, a binary
+//! search finds the second halves that make up the difference. That's about n^(k/2) work and
+//! memory rather than n^k, which is what makes k of 4 or more practical on a few hundred entries.
+use std::iter;
+
+/// A choice of entries that adds up to the target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selection {
+    /// Positions in the report, in increasing order.
+    pub indices: Vec<usize>,
+    /// The entries at those positions.
+    pub values: Vec<i64>,
+}
+
+impl Selection {
+    /// What the puzzle wants: the entries multiplied together.
+    pub fn product(&self) -> i64 {
+        self.values.iter().product()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExpenseReport {
+    entries: Vec<i64>,
+}
+
+impl ExpenseReport {
+    pub fn new(entries: Vec<i64>) -> Self {
+        ExpenseReport { entries }
+    }
+
+    pub fn entries(&self) -> &[i64] {
+        &self.entries
+    }
+
+    /// The first `k` entries (going by their positions) that add up to `target`, or `None` if
+    /// no `k` entries do. An entry can only be used once, but two entries with the same value
+    /// are still different entries.
+    pub fn find(&self, k: usize, target: i64) -> Option<Selection> {
+        self.solutions(k, target).next()
+    }
+
+    /// Every choice of `k` entries that adds up to `target`, ordered by their positions. The
+    /// work happens as the iterator is advanced, so taking just the first one (as `find`
+    /// does) stops as soon as it's found.
+    pub fn solutions(&self, k: usize, target: i64) -> impl Iterator<Item = Selection> + '_ {
+        let n = self.entries.len();
+        let first_half = k / 2;
+        // Sorted by sum, and then by position, so for each first half the matches come out in
+        // order. Every first half needs it, so it's built when the first one comes along, and
+        // not at all if there are fewer than `k` entries.
+        let mut second_halves: Option<Vec<(i64, Vec<usize>)>> = None;
+        let first_halves = if k <= n {
+            Some(combinations(n, first_half))
+        } else {
+            None
+        };
+
+        first_halves.into_iter().flatten().flat_map(move |first| {
+            let second_halves = second_halves.get_or_insert_with(|| {
+                let mut halves: Vec<_> = combinations(n, k - first_half)
+                    .map(|indices| (self.sum(&indices), indices))
+                    .collect();
+                halves.sort_unstable();
+                halves
+            });
+            let needed = target - self.sum(&first);
+            // Each selection is only found once: as its first k/2 positions, followed by
+            // the rest, which all come after them.
+            let after = first.last().map_or(0, |&i| i + 1);
+            let start = second_halves.partition_point(|(sum, _)| *sum < needed);
+            let matches: Vec<Selection> = second_halves[start..]
+                .iter()
+                .take_while(|(sum, _)| *sum == needed)
+                .filter(|(_, second)| !matches!(second.first(), Some(&i) if i < after))
+                .map(|(_, second)| self.selection(first.iter().chain(second).copied().collect()))
+                .collect();
+            matches
+        })
+    }
+
+    fn sum(&self, indices: &[usize]) -> i64 {
+        indices.iter().map(|&i| self.entries[i]).sum()
+    }
+
+    fn selection(&self, indices: Vec<usize>) -> Selection {
+        let values = indices.iter().map(|&i| self.entries[i]).collect();
+        Selection { indices, values }
+    }
+}
+
+/// Every way of choosing `r` of `0..n`, each in increasing order, in lexicographic order.
+fn combinations(n: usize, r: usize) -> impl Iterator<Item = Vec<usize>> {
+    let mut next = if r <= n {
+        Some((0..r).collect::<Vec<_>>())
+    } else {
+        None
+    };
+    iter::from_fn(move || {
+        let current = next.take()?;
+        // Bump the rightmost position that still has room, and pack the rest in after it
+        if let Some(i) = (0..r).rev().find(|&i| current[i] < n - r + i) {
+            let mut advanced = current.clone();
+            advanced[i] += 1;
+            for j in i + 1..r {
+                advanced[j] = advanced[j - 1] + 1;
+            }
+            next = Some(advanced);
+        }
+        Some(current)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_puzzle() {
+        let report = ExpenseReport::new(vec![1721, 979, 366, 299, 675, 1456]);
+        let pair = report.find(2, 2020).unwrap();
+        assert_eq!(pair.indices, vec![0, 3]);
+        assert_eq!(pair.values, vec![1721, 299]);
+        assert_eq!(pair.product(), 514579);
+        assert_eq!(report.find(3, 2020).unwrap().product(), 241861950);
+        assert_eq!(report.find(2, 1), None);
+        assert_eq!(report.find(7, 2020), None);
+    }
+
+    #[test]
+    fn test_solutions() {
+        let report = ExpenseReport::new(vec![1, 2, 3, 4, 5, 3]);
+        let fours: Vec<_> = report.solutions(4, 12).map(|s| s.indices).collect();
+        assert_eq!(
+            fours,
+            vec![vec![0, 1, 3, 4], vec![0, 2, 4, 5], vec![1, 2, 3, 5]]
+        );
+        // The two 3s are different entries, but one 3 can't be used twice
+        let sixes: Vec<_> = report.solutions(2, 6).map(|s| s.indices).collect();
+        assert_eq!(sixes, vec![vec![0, 4], vec![1, 3], vec![2, 5]]);
+        assert_eq!(report.solutions(0, 0).count(), 1);
+        assert_eq!(report.solutions(1, 3).count(), 2);
+        assert_eq!(report.solutions(6, 18).count(), 1);
+        assert_eq!(report.solutions(7, 21).count(), 0);
+    }
+}
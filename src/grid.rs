@@ -0,0 +1,147 @@
+//! A grid that repeats forever to the right (and left), like day 3's map, and straight-line
+//! paths across it.
+//!
+//! A path goes `dx` columns across and `dy` rows down at a time, for any integers other than
+//! `dy == 0`, which would never leave the map. Going down starts at the top left square; going
+//! up (negative `dy`) starts at the bottom left. Either way the path ends when it runs off the
+//! top or bottom.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrappingGrid<T> {
+    width: usize,
+    height: usize,
+    /// Row by row.
+    cells: Vec<T>,
+}
+
+/// One slope, and how it went.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Descent {
+    pub dx: i64,
+    pub dy: i64,
+    /// How many squares on the path were hits, e.g. trees.
+    pub hits: usize,
+    /// Every square the path lands on, as (row, column) with the column inside the grid.
+    pub path: Vec<(usize, usize)>,
+}
+
+impl<T> WrappingGrid<T> {
+    /// Panics if there are no rows, the rows are empty or they aren't all the same width.
+    pub fn new(rows: Vec<Vec<T>>) -> Self {
+        let width = rows.first().map_or(0, Vec::len);
+        assert!(width > 0, "the grid is empty");
+        assert!(
+            rows.iter().all(|row| row.len() == width),
+            "the rows aren't all the same width"
+        );
+        WrappingGrid {
+            width,
+            height: rows.len(),
+            cells: rows.into_iter().flatten().collect(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The square at `row`, `col`, or `None` if `row` is off the top or bottom. The columns
+    /// repeat in both directions, so any `col` is on the grid.
+    pub fn get(&self, row: i64, col: i64) -> Option<&T> {
+        if row < 0 || row >= self.height as i64 {
+            return None;
+        }
+        let col = col.rem_euclid(self.width as i64);
+        Some(&self.cells[row as usize * self.width + col as usize])
+    }
+
+    /// The squares a path with the given slope lands on, as (row, column), with columns
+    /// wrapped into the grid.
+    pub fn path(&self, dx: i64, dy: i64) -> impl Iterator<Item = (usize, usize)> {
+        assert!(dy != 0, "a path with dy = 0 never leaves the grid");
+        let (width, height) = (self.width as i64, self.height as i64);
+        let mut row = if dy > 0 { 0 } else { height - 1 };
+        let mut col = 0;
+        std::iter::from_fn(move || {
+            if row < 0 || row >= height {
+                return None;
+            }
+            let square = (row as usize, col as usize);
+            row += dy;
+            col = (col + dx).rem_euclid(width);
+            Some(square)
+        })
+    }
+
+    /// How many squares on the path with the given slope are hits.
+    pub fn count<F: Fn(&T) -> bool>(&self, dx: i64, dy: i64, is_hit: F) -> usize {
+        self.path(dx, dy)
+            .filter(|&(row, col)| is_hit(&self.cells[row * self.width + col]))
+            .count()
+    }
+
+    /// Every slope with `dx` and `dy` between `-bound` and `bound` (apart from `dy == 0`),
+    /// ordered from the fewest hits to the most. Ties go downhill before uphill, then shallow
+    /// before steep, then left to right, so the order is the same from run to run.
+    pub fn rank_slopes<F: Fn(&T) -> bool>(&self, bound: i64, is_hit: F) -> Vec<Descent> {
+        let mut descents = vec![];
+        for dy in (-bound..=bound).filter(|&dy| dy != 0) {
+            for dx in -bound..=bound {
+                let path: Vec<_> = self.path(dx, dy).collect();
+                let hits = path
+                    .iter()
+                    .filter(|&&(row, col)| is_hit(&self.cells[row * self.width + col]))
+                    .count();
+                descents.push(Descent { dx, dy, hits, path });
+            }
+        }
+        descents.sort_by_key(|d| (d.hits, d.dy < 0, d.dy.abs(), d.dx));
+        descents
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn grid() -> WrappingGrid<bool> {
+        let rows = ["..#", "#..", ".#."];
+        WrappingGrid::new(
+            rows.iter()
+                .map(|row| row.chars().map(|c| c == '#').collect())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_paths() {
+        let grid = grid();
+        assert_eq!(grid.get(1, -3), Some(&true));
+        assert_eq!(grid.get(0, 5), Some(&true));
+        assert_eq!(grid.get(3, 0), None);
+        assert_eq!(
+            grid.path(1, 1).collect::<Vec<_>>(),
+            vec![(0, 0), (1, 1), (2, 2)]
+        );
+        assert_eq!(
+            grid.path(-1, 1).collect::<Vec<_>>(),
+            vec![(0, 0), (1, 2), (2, 1)]
+        );
+        assert_eq!(grid.path(4, -2).collect::<Vec<_>>(), vec![(2, 0), (0, 1)]);
+        assert_eq!(grid.count(-1, 1, |&tree| tree), 1);
+    }
+
+    #[test]
+    fn test_rank_slopes() {
+        let ranked = grid().rank_slopes(1, |&tree| tree);
+        assert_eq!(ranked.len(), 6);
+        assert!(ranked.windows(2).all(|w| w[0].hits <= w[1].hits));
+        assert_eq!((ranked[0].dx, ranked[0].dy, ranked[0].hits), (1, 1, 0));
+        // (-1, -1) also misses everything, but going down comes first
+        assert_eq!((ranked[1].dx, ranked[1].dy, ranked[1].hits), (-1, -1, 0));
+        assert_eq!(ranked[5].hits, 1);
+    }
+}
@@ -1,34 +1,60 @@
+//! Solutions to Advent of Code 2020.
+//!
+//! Each `dayN` module has the day's parser (the `#[aoc_generator]` function, which returns the
+//! parsed input type), a solver for each part (the `#[aoc]` functions), and whatever types and
+//! simulations the solvers are built on, e.g. `day8::Machine`, `day17::Grid4D` or `day23::Cups`.
+//! Everything else is an implementation detail and stays private.
+//!
+//! Each day also has a unit struct (`day8::Day8` etc.) implementing `solution::Solution`, which
+//! ties its parser and solvers together, and `solution::DAYS` lists them all.
 extern crate aoc_runner;
 
 #[macro_use]
 extern crate aoc_runner_derive;
 
-mod day1;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
+// Declared first so the logging macros are in scope in every day's module.
+#[macro_use]
+pub mod log;
+
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
 
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
 
-mod day20;
-mod day21;
-mod day22;
-mod day23;
-mod day24;
-mod day25;
+pub mod adapters;
+pub mod answers;
+pub mod bench;
+pub mod console;
+pub mod error;
+pub mod example;
+pub mod expenses;
+pub mod grid;
+pub mod lint;
+pub mod passport;
+pub mod runner;
+pub mod solution;
 
 aoc_lib! { year = 2020 }
@@ -0,0 +1,145 @@
+//! Checks that an input has the properties some solvers take for granted.
+//!
+//! A few solvers lean on shortcuts that hold for the puzzle inputs we've seen, but aren't
+//! promised by the puzzle descriptions: day 13 assumes the bus IDs are prime, day 20 that
+//! tile edges pair up uniquely, and so on. On an input where one doesn't hold, the solver
+//! panics on an `unwrap()` or gives a wrong answer. Each of those days has an `assumptions`
+//! function next to its solvers, which checks them and explains any that fail.
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::hash::Hash;
+
+use crate::error::ParseError;
+use crate::*;
+
+/// One assumption a solver makes, and whether the input bears it out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    /// The solver that relies on the assumption, e.g. "day13::day2".
+    pub solver: &'static str,
+    pub assumption: &'static str,
+    /// Why the assumption doesn't hold, or `None` if it does.
+    pub failure: Option<String>,
+}
+
+impl Check {
+    /// A check that fails with the given reasons, or passes if there aren't any. Long lists
+    /// of reasons are cut short.
+    pub fn new(solver: &'static str, assumption: &'static str, reasons: Vec<String>) -> Self {
+        const SHOWN: usize = 5;
+        let failure = match reasons.len() {
+            0 => None,
+            n if n <= SHOWN => Some(reasons.join("; ")),
+            n => Some(format!(
+                "{}; and {} more",
+                reasons[..SHOWN].join("; "),
+                n - SHOWN
+            )),
+        };
+        Check {
+            solver,
+            assumption,
+            failure,
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+/// The days that have assumptions to check.
+pub const DAYS: [u32; 5] = [13, 16, 19, 20, 21];
+
+/// Parses `input` as the given day's input and checks the day's assumptions against it.
+/// Days that don't make any assumptions have no checks.
+pub fn lint(day: u32, input: &str) -> Result<Vec<Check>, ParseError> {
+    let input = input.trim_end_matches('\n');
+    Ok(match day {
+        13 => day13::assumptions(&day13::parse2(input)?),
+        16 => day16::assumptions(&day16::parse(input)?),
+        19 => day19::assumptions(&day19::gen(input)?.0),
+        20 => day20::assumptions(&day20::parse(input)?),
+        21 => day21::assumptions(&day21::parse(input)?),
+        _ => vec![],
+    })
+}
+
+/// Simulates the constraint solving in days 16 and 21: repeatedly find a key with only one
+/// candidate left, assign it that candidate, and take the candidate away from every other
+/// key. Gives back whatever's left when there's no key with exactly one candidate, which is
+/// empty if it solved everything.
+pub fn eliminate<K, V>(mut candidates: HashMap<K, HashSet<V>>) -> HashMap<K, HashSet<V>>
+where
+    K: Clone + Eq + Hash,
+    V: Clone + Eq + Hash,
+{
+    while let Some((key, value)) = candidates
+        .iter()
+        .find(|(_, values)| values.len() == 1)
+        .map(|(key, values)| (key.clone(), values.iter().next().unwrap().clone()))
+    {
+        candidates.remove(&key);
+        for values in candidates.values_mut() {
+            values.remove(&value);
+        }
+    }
+    candidates
+}
+
+/// Explains what `eliminate` got stuck on, one reason per key, in a stable order.
+pub fn unresolved<K, V>(leftover: &HashMap<K, HashSet<V>>) -> Vec<String>
+where
+    K: Display,
+    V: Display,
+{
+    let mut reasons: Vec<String> = leftover
+        .iter()
+        .map(|(key, values)| {
+            if values.is_empty() {
+                return format!("{} has no candidates left", key);
+            }
+            let mut values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+            values.sort_unstable();
+            format!("{} could be any of {}", key, values.join(", "))
+        })
+        .collect();
+    reasons.sort_unstable();
+    reasons
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_eliminate() {
+        let candidates = |pairs: &[(&'static str, &[u32])]| -> HashMap<_, HashSet<_>> {
+            pairs
+                .iter()
+                .map(|(k, vs)| (*k, vs.iter().copied().collect()))
+                .collect()
+        };
+        let solvable = candidates(&[("a", &[1, 2]), ("b", &[2]), ("c", &[1, 2, 3])]);
+        assert!(eliminate(solvable).is_empty());
+        let stuck = candidates(&[("a", &[1, 2]), ("b", &[1, 2]), ("c", &[3])]);
+        assert_eq!(eliminate(stuck).len(), 2);
+    }
+
+    #[test]
+    fn test_lint() {
+        let checks = lint(13, "939\n7,13,x,x,59,x,31,19").unwrap();
+        assert!(checks.iter().all(Check::passed), "{:?}", checks);
+        let checks = lint(13, "939\n6,x,9,7").unwrap();
+        let failures: Vec<_> = checks.iter().filter_map(|c| c.failure.as_ref()).collect();
+        assert_eq!(
+            failures,
+            vec![
+                "6 isn't prime; 9 isn't prime",
+                "6 and 9 have a common factor of 3"
+            ]
+        );
+        assert!(lint(1, "1721").unwrap().is_empty());
+        assert!(lint(20, "Tile 1:\n#").is_err());
+    }
+}
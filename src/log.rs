@@ -0,0 +1,179 @@
+//! Debug output for the solvers, off by default.
+//!
+//! Solvers log with the `info!`, `debug!` and `trace!` macros, which work like `eprintln!`
+//! but only print if logging is turned on for that day at that level. Each day gets its own
+//! level, worked out from the module the macro is called in, so turning on `trace` for day 15
+//! doesn't bury you in output from every other day. Everything goes to stderr, so stdout only
+//! ever has answers on it.
+//!
+//! When logging is off, each macro costs one atomic load, and its arguments aren't evaluated.
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Off = 0,
+    /// The interesting conclusions a solver reaches along the way.
+    Info = 1,
+    /// Intermediate results, a handful of lines per part.
+    Debug = 2,
+    /// Every step of a simulation; can be a lot of output.
+    Trace = 3,
+}
+
+impl Level {
+    fn from_u8(level: u8) -> Level {
+        match level {
+            0 => Level::Off,
+            1 => Level::Info,
+            2 => Level::Debug,
+            _ => Level::Trace,
+        }
+    }
+
+    fn parse(s: &str) -> Option<Level> {
+        match s {
+            "off" => Some(Level::Off),
+            "info" => Some(Level::Info),
+            "debug" => Some(Level::Debug),
+            "trace" => Some(Level::Trace),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Level::Off => "OFF",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        }
+    }
+}
+
+// Index 0 is used for code that isn't in any day's module.
+#[allow(clippy::declare_interior_mutable_const)]
+const OFF: AtomicU8 = AtomicU8::new(0);
+static LEVELS: [AtomicU8; 26] = [OFF; 26];
+// The highest level set for any day, so that the common case of logging being off everywhere
+// is a single check.
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(0);
+
+/// Sets the level for one day, or for every day if `day` is `None`.
+pub fn set_level(day: Option<u32>, level: Level) {
+    match day {
+        Some(day) => LEVELS[day as usize].store(level as u8, Ordering::Relaxed),
+        None => LEVELS
+            .iter()
+            .for_each(|l| l.store(level as u8, Ordering::Relaxed)),
+    }
+    let max = LEVELS
+        .iter()
+        .map(|l| l.load(Ordering::Relaxed))
+        .max()
+        .unwrap();
+    MAX_LEVEL.store(max, Ordering::Relaxed);
+}
+
+pub fn level(day: u32) -> Level {
+    Level::from_u8(LEVELS[day as usize].load(Ordering::Relaxed))
+}
+
+/// Sets levels from a comma-separated spec like "debug" or "info,day15=trace,day7=off".
+/// A bare level applies to every day; later entries override earlier ones.
+pub fn configure(spec: &str) -> Result<(), String> {
+    for part in spec.split(',') {
+        let (day, level) = match part.find('=') {
+            Some(i) => {
+                let day = part[..i]
+                    .strip_prefix("day")
+                    .and_then(|d| d.parse().ok())
+                    .filter(|d| (1..=25).contains(d))
+                    .ok_or_else(|| format!("expected day1 to day25, found {:?}", &part[..i]))?;
+                (Some(day), &part[i + 1..])
+            }
+            None => (None, part),
+        };
+        let level = Level::parse(level).ok_or_else(|| {
+            format!(
+                "expected one of off, info, debug or trace, found {:?}",
+                level
+            )
+        })?;
+        set_level(day, level);
+    }
+    Ok(())
+}
+
+/// Works out which day a module belongs to from its path, e.g. 19 for "aoc2020::day19::part2".
+pub fn day_of(module_path: &str) -> Option<u32> {
+    module_path
+        .split("::")
+        .find_map(|segment| segment.strip_prefix("day")?.parse().ok())
+        .filter(|day| (1..=25).contains(day))
+}
+
+/// Whether the macros called from `module_path` should print at `level`.
+pub fn enabled(module_path: &str, level: Level) -> bool {
+    if level as u8 > MAX_LEVEL.load(Ordering::Relaxed) {
+        return false;
+    }
+    level <= self::level(day_of(module_path).unwrap_or(0))
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log {
+    ($level:expr, $($arg:tt)*) => {
+        if $crate::log::enabled(module_path!(), $level) {
+            eprintln!(
+                "[{} {}] {}",
+                module_path!().rsplit("::").next().unwrap(),
+                $level.name(),
+                format_args!($($arg)*)
+            );
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)*) => { $crate::__log!($crate::log::Level::Info, $($arg)*) };
+}
+
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)*) => { $crate::__log!($crate::log::Level::Debug, $($arg)*) };
+}
+
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)*) => { $crate::__log!($crate::log::Level::Trace, $($arg)*) };
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_day_of() {
+        assert_eq!(day_of("aoc2020::day7"), Some(7));
+        assert_eq!(day_of("aoc2020::day19::part2"), Some(19));
+        assert_eq!(day_of("aoc2020::runner"), None);
+        assert_eq!(day_of("aoc2020::day99"), None);
+    }
+
+    // The levels are global, so everything touching them lives in this one test.
+    #[test]
+    fn test_configure() {
+        assert!(!enabled("aoc2020::day15", Level::Info));
+        configure("info,day15=trace,day7=off").unwrap();
+        assert!(enabled("aoc2020::day15", Level::Trace));
+        assert!(enabled("aoc2020::day3", Level::Info));
+        assert!(!enabled("aoc2020::day3", Level::Debug));
+        assert!(!enabled("aoc2020::day7", Level::Info));
+        assert!(configure("day26=debug").is_err());
+        assert!(configure("loud").is_err());
+        configure("off").unwrap();
+        assert!(!enabled("aoc2020::day15", Level::Info));
+    }
+}
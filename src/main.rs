@@ -1,6 +1,797 @@
-extern crate aoc_runner;
-extern crate aoc_runner_derive;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{self, BufRead, Read, Write};
+use std::ops::RangeInclusive;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::Duration;
+
+use aoc2020::answers::{self, AnswerStore, Verdict};
+use aoc2020::bench::{self, Report};
+use aoc2020::console::{self, Breakpoint, Debugger, Instruction, InstructionSet, Machine, Stop};
+use aoc2020::day15;
+use aoc2020::example;
+use aoc2020::lint;
+use aoc2020::log;
+use aoc2020::runner::{self, Answer, Config};
+
+const USAGE: &str = "Usage:
+    aoc2020 <day> [<part>] [<input>]    run one day (both parts if <part> is left out)
+    aoc2020 all [<input-dir>]           run every day in sequence
+    aoc2020 example [<day>]             check the solvers against the examples in the puzzle descriptions
+    aoc2020 verify [<input-dir>] [--answers <file>] [--record]
+                                        check every day's answers against the ones recorded in answers.tsv;
+                                        --record saves the answers that weren't recorded yet
+    aoc2020 batch <day> <dir>           run one day on every input file in <dir>, and tabulate the answers
+    aoc2020 bench [<day>] [<input-dir>] [--runs <n>] [--report <file>] [--baseline <file>] [--threshold <percent>]
+                                        time the parser and solvers of one day or every day, <n> times each (default 5);
+                                        --report saves the timings (as CSV if <file> ends in .csv, JSON otherwise),
+                                        --baseline compares them against an earlier report, and fails if any
+                                        median is more than <percent> slower (default 20)
+    aoc2020 trace [<input>] [--json]    run a day 8 console program until it ends or loops, printing every
+                                        instruction run, with the accumulator after it
+    aoc2020 debug [<input>]             step through a day 8 console program interactively (type h for help)
+    aoc2020 listing [<input>]           print a day 8 console program with labelled jump targets, marking
+                                        infinite loops and unreachable code
+    aoc2020 lint [<day>] [<input>]      check the assumptions a day's solvers make about their input, and explain
+                                        any that don't hold (every day that makes any, if <day> is left out)
+
+Options:
+    -v, --verbose       print debug output from the solvers to stderr
+    --log <levels>      set how much the solvers print, overall or per day, e.g. \"info,day15=trace\"
+                        (levels are off, info, debug and trace)
+    --day15-engine <e>  how day 15 remembers the numbers spoken: array (the default) or hash
+
+<input> is the path to a puzzle input, or - to read it from stdin.
+By default inputs are read from input/2020/day<day>.txt, which is where `cargo aoc` keeps them.";
 
 fn main() {
-    println!("Hello, world!");
+    let (args, config) = match take_global_options(std::env::args().skip(1).collect()) {
+        Ok(taken) => taken,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+    let code = match args.first().map(String::as_str) {
+        None | Some("-h") | Some("--help") => {
+            println!("{}", USAGE);
+            0
+        }
+        Some("all") => run_all(&config, args.get(1).map(PathBuf::from)),
+        Some("example") => match args.get(1).map(|day| day.parse()) {
+            None => run_examples(1..=25),
+            Some(Ok(day)) if args.len() == 2 => run_examples(day..=day),
+            _ => {
+                eprintln!("{}", USAGE);
+                2
+            }
+        },
+        Some("verify") => match parse_verify_args(&args[1..]) {
+            Some((dir, answers, record)) => run_verify(&config, dir, answers, record),
+            None => {
+                eprintln!("{}", USAGE);
+                2
+            }
+        },
+        Some("batch") => match (args.get(1).and_then(|d| d.parse().ok()), args.get(2)) {
+            (Some(day), Some(dir)) if args.len() == 3 && (1..=25).contains(&day) => {
+                run_batch(&config, day, Path::new(dir))
+            }
+            _ => {
+                eprintln!("{}", USAGE);
+                2
+            }
+        },
+        Some("bench") => match parse_bench_args(&args[1..]) {
+            Some(options) => run_bench(&config, options),
+            None => {
+                eprintln!("{}", USAGE);
+                2
+            }
+        },
+        Some("trace") => match args[1..] {
+            [] => run_trace(None, false),
+            [ref flag] if flag == "--json" => run_trace(None, true),
+            [ref input] => run_trace(Some(input.clone()), false),
+            [ref input, ref flag] if flag == "--json" => run_trace(Some(input.clone()), true),
+            _ => {
+                eprintln!("{}", USAGE);
+                2
+            }
+        },
+        Some("debug") if args.len() <= 2 => run_debug(args.get(1).cloned()),
+        Some("listing") if args.len() <= 2 => match load_program(args.get(1).cloned()) {
+            Some(program) => {
+                print!("{}", console::listing(&program));
+                0
+            }
+            None => 1,
+        },
+        Some("lint") => match args[1..] {
+            [] => run_lint_all(),
+            [ref day] | [ref day, _] => match day.parse() {
+                Ok(day) if (1..=25).contains(&day) => run_lint(day, args.get(2).cloned()),
+                _ => {
+                    eprintln!("{}", USAGE);
+                    2
+                }
+            },
+            _ => {
+                eprintln!("{}", USAGE);
+                2
+            }
+        },
+        Some(_) => match parse_day_args(&args) {
+            Some((day, part, input)) => run_day(&config, day, part, input),
+            None => {
+                eprintln!("{}", USAGE);
+                2
+            }
+        },
+    };
+    process::exit(code);
+}
+
+/// Pulls the logging options and `--day15-engine` out of the arguments, wherever they are.
+/// The logging options are applied straight away; the rest end up in the `Config`.
+fn take_global_options(args: Vec<String>) -> Result<(Vec<String>, Config), String> {
+    let mut rest = vec![];
+    let mut config = Config::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-v" | "--verbose" => log::set_level(None, log::Level::Debug),
+            "--log" => {
+                let spec = args.next().ok_or("--log needs a value")?;
+                log::configure(&spec)?;
+            }
+            "--day15-engine" => {
+                let name = args.next().ok_or("--day15-engine needs a value")?;
+                config.day15_engine = day15::Engine::parse(&name)
+                    .ok_or_else(|| format!("expected hash or array, found {:?}", name))?;
+            }
+            _ => match arg.strip_prefix("--log=") {
+                Some(spec) => log::configure(spec)?,
+                None => rest.push(arg),
+            },
+        }
+    }
+    Ok((rest, config))
+}
+
+/// Parses `<day> [<part>] [<input>]`. The part is optional, so a second argument that isn't
+/// 1 or 2 is taken to be the input.
+fn parse_day_args(args: &[String]) -> Option<(u32, Option<u32>, Option<String>)> {
+    if args.len() > 3 {
+        return None;
+    }
+    let day = args[0].parse().ok().filter(|d| (1..=25).contains(d))?;
+    match (args.get(1), args.get(2)) {
+        (None, _) => Some((day, None, None)),
+        (Some(part), input) if part == "1" || part == "2" => {
+            Some((day, Some(part.parse().unwrap()), input.cloned()))
+        }
+        (Some(input), None) => Some((day, None, Some(input.clone()))),
+        _ => None,
+    }
+}
+
+/// Parses `[<input-dir>] [--answers <file>] [--record]`, in any order.
+fn parse_verify_args(args: &[String]) -> Option<(PathBuf, PathBuf, bool)> {
+    let mut dir = None;
+    let mut answers = None;
+    let mut record = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--record" => record = true,
+            "--answers" => answers = Some(PathBuf::from(args.next()?)),
+            _ if dir.is_none() && !arg.starts_with("--") => dir = Some(PathBuf::from(arg)),
+            _ => return None,
+        }
+    }
+    Some((
+        dir.unwrap_or_else(|| PathBuf::from(runner::DEFAULT_INPUT_DIR)),
+        answers.unwrap_or_else(|| PathBuf::from(answers::DEFAULT_ANSWERS_FILE)),
+        record,
+    ))
+}
+
+struct BenchOptions {
+    days: RangeInclusive<u32>,
+    dir: PathBuf,
+    runs: usize,
+    report: Option<PathBuf>,
+    baseline: Option<PathBuf>,
+    threshold: f64,
+}
+
+/// Parses `[<day>] [<input-dir>] [--runs <n>] [--report <file>] [--baseline <file>]
+/// [--threshold <percent>]`, in any order.
+fn parse_bench_args(args: &[String]) -> Option<BenchOptions> {
+    let mut options = BenchOptions {
+        days: 1..=25,
+        dir: PathBuf::from(runner::DEFAULT_INPUT_DIR),
+        runs: 5,
+        report: None,
+        baseline: None,
+        threshold: 20.0,
+    };
+    let (mut day, mut dir) = (None, None);
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--runs" => options.runs = args.next()?.parse().ok().filter(|&n| n > 0)?,
+            "--report" => options.report = Some(PathBuf::from(args.next()?)),
+            "--baseline" => options.baseline = Some(PathBuf::from(args.next()?)),
+            "--threshold" => options.threshold = args.next()?.parse().ok()?,
+            _ if arg.starts_with("--") => return None,
+            _ => match arg.parse::<u32>() {
+                Ok(d) if day.is_none() && (1..=25).contains(&d) => day = Some(d),
+                _ if dir.is_none() => dir = Some(PathBuf::from(arg)),
+                _ => return None,
+            },
+        }
+    }
+    if let Some(day) = day {
+        options.days = day..=day;
+    }
+    if let Some(dir) = dir {
+        options.dir = dir;
+    }
+    Some(options)
+}
+
+fn read_input(path: &str) -> io::Result<String> {
+    if path == "-" {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input)?;
+        Ok(input)
+    } else {
+        fs::read_to_string(path)
+    }
+}
+
+fn run_day(config: &Config, day: u32, part: Option<u32>, input: Option<String>) -> i32 {
+    let path = input.unwrap_or_else(|| {
+        runner::input_path(Path::new(runner::DEFAULT_INPUT_DIR), day)
+            .to_string_lossy()
+            .into_owned()
+    });
+    let input = match read_input(&path) {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("error: couldn't read {}: {}", path, e);
+            return 1;
+        }
+    };
+    match part {
+        // A single part prints just the answer, so it's easy to use from scripts.
+        Some(part) => match panic::catch_unwind(|| runner::run_with(config, day, part, &input)) {
+            Ok(Ok(answer)) => {
+                println!("{}", answer.value);
+                0
+            }
+            Ok(Err(e)) => {
+                eprintln!("error: {}", e);
+                1
+            }
+            Err(_) => {
+                eprintln!("Day {} - Part {}: FAILED: solver panicked", day, part);
+                1
+            }
+        },
+        None => run_parts(config, day, &input),
+    }
+}
+
+fn run_all(config: &Config, dir: Option<PathBuf>) -> i32 {
+    let dir = dir.unwrap_or_else(|| PathBuf::from(runner::DEFAULT_INPUT_DIR));
+    let mut code = 0;
+    for day in 1..=25 {
+        let path = runner::input_path(&dir, day);
+        match fs::read_to_string(&path) {
+            Ok(input) => code = code.max(run_parts(config, day, &input)),
+            Err(e) => {
+                eprintln!("Day {}: couldn't read {}: {}", day, path.display(), e);
+                code = 1;
+            }
+        }
+    }
+    code
+}
+
+/// Runs every part of a day, carrying on to the next part if one of them fails or panics.
+fn run_parts(config: &Config, day: u32, input: &str) -> i32 {
+    let mut code = 0;
+    for &part in runner::parts(day) {
+        match panic::catch_unwind(|| runner::run_with(config, day, part, input)) {
+            Ok(Ok(Answer {
+                value,
+                generator,
+                runner,
+            })) => println!(
+                "Day {} - Part {}: {} (generator: {:?}, runner: {:?})",
+                day, part, value, generator, runner
+            ),
+            Ok(Err(e)) => {
+                eprintln!("Day {} - Part {}: FAILED: {}", day, part, e);
+                code = 1;
+            }
+            Err(_) => {
+                eprintln!("Day {} - Part {}: FAILED: solver panicked", day, part);
+                code = 1;
+            }
+        }
+    }
+    code
+}
+
+/// Runs every registered example for the given days, and fails if any answer doesn't match the
+/// one from the puzzle description.
+fn run_examples(days: RangeInclusive<u32>) -> i32 {
+    let mut code = 0;
+    for day in days {
+        let examples = example::examples(day);
+        if examples.is_empty() {
+            println!("Day {}: no examples", day);
+        }
+        for (i, ex) in examples.iter().enumerate() {
+            let label = format!("Day {} - Part {} - example {}", day, ex.part, i + 1);
+            match panic::catch_unwind(|| runner::run_example(day, ex)) {
+                Ok(Ok(answer)) if answer.value == ex.expected => {
+                    println!("{}: {} (ok)", label, answer.value)
+                }
+                Ok(Ok(answer)) => {
+                    println!("{}: {} (expected {})", label, answer.value, ex.expected);
+                    code = 1;
+                }
+                Ok(Err(e)) => {
+                    eprintln!("{}: FAILED: {}", label, e);
+                    code = 1;
+                }
+                Err(_) => {
+                    eprintln!("{}: FAILED: solver panicked", label);
+                    code = 1;
+                }
+            }
+        }
+    }
+    code
+}
+
+/// Runs every part of every day, and compares the answers against the ones recorded for that
+/// input. A wrong answer or a failing solver is an error; a day with no recorded answer
+/// (or no input at all) is reported, but doesn't fail the run unless nothing was checked at all.
+fn run_verify(config: &Config, dir: PathBuf, answers_path: PathBuf, record: bool) -> i32 {
+    let mut store = match AnswerStore::load(&answers_path) {
+        Ok(store) => store,
+        Err(e) => {
+            eprintln!("error: couldn't read {}: {}", answers_path.display(), e);
+            return 1;
+        }
+    };
+    let (mut passed, mut failed, mut missing, mut recorded) = (0, 0, 0, 0);
+    for day in 1..=25 {
+        let path = runner::input_path(&dir, day);
+        let input = match fs::read_to_string(&path) {
+            Ok(input) => input,
+            Err(e) => {
+                println!("Day {}: missing ({}: {})", day, path.display(), e);
+                missing += runner::parts(day).len();
+                continue;
+            }
+        };
+        let fingerprint = answers::fingerprint(&input);
+        for &part in runner::parts(day) {
+            let label = format!("Day {} - Part {}", day, part);
+            let value = match panic::catch_unwind(|| runner::run_with(config, day, part, &input)) {
+                Ok(Ok(answer)) => answer.value,
+                Ok(Err(e)) => {
+                    println!("{}: FAIL: {}", label, e);
+                    failed += 1;
+                    continue;
+                }
+                Err(_) => {
+                    println!("{}: FAIL: solver panicked", label);
+                    failed += 1;
+                    continue;
+                }
+            };
+            match store.check(&fingerprint, day, part, &value) {
+                Verdict::Pass => {
+                    println!("{}: pass", label);
+                    passed += 1;
+                }
+                Verdict::Fail { expected } => {
+                    println!("{}: FAIL: got {}, expected {}", label, value, expected);
+                    failed += 1;
+                }
+                Verdict::Missing if record => {
+                    println!("{}: recorded {}", label, value);
+                    store.insert(&fingerprint, day, part, &value);
+                    recorded += 1;
+                }
+                Verdict::Missing => {
+                    println!("{}: missing (got {})", label, value);
+                    missing += 1;
+                }
+            }
+        }
+    }
+    println!(
+        "{} passed, {} failed, {} missing, {} recorded",
+        passed, failed, missing, recorded
+    );
+    if recorded > 0 {
+        if let Err(e) = store.save(&answers_path) {
+            eprintln!("error: couldn't write {}: {}", answers_path.display(), e);
+            return 1;
+        }
+    }
+    if failed > 0 {
+        return 1;
+    }
+    // Missing answers aren't a failure one at a time, but a run that checked nothing at all
+    // proves nothing, and shouldn't look like it passed.
+    if passed == 0 && recorded == 0 {
+        eprintln!(
+            "error: nothing was verified: there are no recorded answers for any input in {}; \
+             run `aoc2020 verify --record` against known-good solvers to record them",
+            dir.display()
+        );
+        return 1;
+    }
+    0
+}
+
+fn run_bench(config: &Config, options: BenchOptions) -> i32 {
+    let mut code = 0;
+    let mut report = Report::default();
+    for day in options.days.clone() {
+        let path = runner::input_path(&options.dir, day);
+        let input = match fs::read_to_string(&path) {
+            Ok(input) => input,
+            Err(e) => {
+                eprintln!("Day {}: couldn't read {}: {}", day, path.display(), e);
+                code = 1;
+                continue;
+            }
+        };
+        let solution = config.day(day).unwrap();
+        match panic::catch_unwind(|| bench::bench_day(solution, &input, options.runs)) {
+            Ok(Ok(timings)) => {
+                for t in &timings {
+                    println!(
+                        "Day {:>2} {}: median {:?} (min {:?}, mean {:?})",
+                        t.day,
+                        t.stage,
+                        Duration::from_nanos(t.median_ns),
+                        Duration::from_nanos(t.min_ns),
+                        Duration::from_nanos(t.mean_ns)
+                    );
+                }
+                report.timings.extend(timings);
+            }
+            Ok(Err(e)) => {
+                eprintln!("Day {}: FAILED: {}", day, e);
+                code = 1;
+            }
+            Err(_) => {
+                eprintln!("Day {}: FAILED: solver panicked", day);
+                code = 1;
+            }
+        }
+    }
+    if let Some(path) = &options.report {
+        if let Err(e) = report.save(path) {
+            eprintln!("error: couldn't write {}: {}", path.display(), e);
+            code = 1;
+        }
+    }
+    if let Some(path) = &options.baseline {
+        let baseline = match Report::load(path) {
+            Ok(baseline) => baseline,
+            Err(e) => {
+                eprintln!("error: couldn't read {}: {}", path.display(), e);
+                return 1;
+            }
+        };
+        println!();
+        for c in report.compare(&baseline, options.threshold / 100.0) {
+            println!(
+                "Day {:>2} {}: {:?} -> {:?} ({:+.1}%){}",
+                c.day,
+                c.stage,
+                Duration::from_nanos(c.baseline_ns),
+                Duration::from_nanos(c.current_ns),
+                c.change * 100.0,
+                if c.regression { " REGRESSION" } else { "" }
+            );
+            if c.regression {
+                code = 1;
+            }
+        }
+    }
+    code
+}
+
+/// Runs one day on every file in `dir`, printing a row per file with each part's answer and how
+/// long it took. Different inputs giving the same answer is a sign a solver has something about
+/// one particular input hardcoded, so we point that out too.
+fn run_batch(config: &Config, day: u32, dir: &Path) -> i32 {
+    let mut paths: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| path.is_file())
+            .collect(),
+        Err(e) => {
+            eprintln!("error: couldn't read {}: {}", dir.display(), e);
+            return 1;
+        }
+    };
+    paths.sort();
+    let parts = runner::parts(day);
+    let mut header = vec!["input".to_owned(), "parse".to_owned()];
+    for part in parts {
+        header.push(format!("part {}", part));
+        header.push("time".to_owned());
+    }
+    let mut rows = vec![header];
+    let mut code = 0;
+    // for each part, the answer for each distinct input
+    let mut answers: Vec<HashMap<String, String>> = vec![HashMap::new(); parts.len()];
+    for path in &paths {
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        let input = match fs::read_to_string(path) {
+            Ok(input) => input,
+            Err(e) => {
+                rows.push(vec![name, format!("FAILED: {}", e)]);
+                code = 1;
+                continue;
+            }
+        };
+        let fingerprint = answers::fingerprint(&input);
+        let mut row = vec![name, String::new()];
+        for (i, &part) in parts.iter().enumerate() {
+            match panic::catch_unwind(|| runner::run_with(config, day, part, &input)) {
+                Ok(Ok(answer)) => {
+                    row[1] = format!("{:?}", answer.generator);
+                    row.push(answer.value.clone());
+                    row.push(format!("{:?}", answer.runner));
+                    answers[i].insert(fingerprint.clone(), answer.value);
+                }
+                Ok(Err(e)) => {
+                    row.push(format!("FAILED: {}", e));
+                    row.push(String::new());
+                    code = 1;
+                }
+                Err(_) => {
+                    row.push("FAILED: solver panicked".to_owned());
+                    row.push(String::new());
+                    code = 1;
+                }
+            }
+        }
+        rows.push(row);
+    }
+    print_table(&rows);
+    for (answers, part) in answers.iter().zip(parts) {
+        let distinct: HashSet<_> = answers.values().collect();
+        if answers.len() > 1 && distinct.len() == 1 {
+            println!(
+                "warning: all {} different inputs gave the same answer to part {}",
+                answers.len(),
+                part
+            );
+        }
+    }
+    code
+}
+
+/// Checks one day's assumptions, and fails if any of them don't hold for the input.
+fn run_lint(day: u32, input: Option<String>) -> i32 {
+    let path = input.unwrap_or_else(|| {
+        runner::input_path(Path::new(runner::DEFAULT_INPUT_DIR), day)
+            .to_string_lossy()
+            .into_owned()
+    });
+    let input = match read_input(&path) {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("error: couldn't read {}: {}", path, e);
+            return 1;
+        }
+    };
+    let checks = match lint::lint(day, &input) {
+        Ok(checks) => checks,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return 1;
+        }
+    };
+    if checks.is_empty() {
+        println!("Day {}: no assumptions to check", day);
+    }
+    let mut code = 0;
+    for check in checks {
+        match check.failure {
+            None => println!("Day {}: ok    {}: {}", day, check.solver, check.assumption),
+            Some(failure) => {
+                println!("Day {}: FAIL  {}: {}", day, check.solver, check.assumption);
+                println!("            {}", failure);
+                code = 1;
+            }
+        }
+    }
+    code
+}
+
+/// Checks every day that makes assumptions against its default input. Like `verify`, a
+/// missing input is reported but isn't a failure.
+fn run_lint_all() -> i32 {
+    let mut code = 0;
+    for &day in lint::DAYS.iter() {
+        let path = runner::input_path(Path::new(runner::DEFAULT_INPUT_DIR), day);
+        if !path.exists() {
+            println!("Day {}: no input at {}", day, path.display());
+            continue;
+        }
+        code = code.max(run_lint(day, Some(path.to_string_lossy().into_owned())));
+    }
+    code
+}
+
+/// Reads and assembles a console program, by default day 8's input. The assembler takes
+/// labels and comments as well as the puzzle's plain format.
+fn load_program(input: Option<String>) -> Option<Vec<Instruction>> {
+    let path = input.unwrap_or_else(|| {
+        runner::input_path(Path::new(runner::DEFAULT_INPUT_DIR), 8)
+            .to_string_lossy()
+            .into_owned()
+    });
+    let input = match read_input(&path) {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("error: couldn't read {}: {}", path, e);
+            return None;
+        }
+    };
+    match InstructionSet::new().assemble(&input) {
+        Ok(program) => Some(program),
+        Err(e) => {
+            eprintln!("error: {}, {}", path, e);
+            None
+        }
+    }
+}
+
+fn run_trace(input: Option<String>, json: bool) -> i32 {
+    let program = match load_program(input) {
+        Some(program) => program,
+        None => return 1,
+    };
+    let mut machine = Machine::new(program).with_trace();
+    let halt = machine.run_until_repeat();
+    let trace = machine.trace().unwrap();
+    if json {
+        println!("{}", trace.to_json());
+    } else {
+        print!("{}", trace.to_text());
+    }
+    eprintln!("halted: {:?}, acc={}", halt, machine.acc());
+    0
+}
+
+const DEBUG_HELP: &str = "\
+    s [<n>]     run the next instruction, or the next <n>
+    c           continue until a breakpoint, or the program halts or repeats an instruction
+    b <bp>      set a breakpoint, either a pc (e.g. b 12) or an accumulator value (e.g. b acc=-4)
+    d <bp>      delete a breakpoint
+    i           show the registers and breakpoints
+    t           show every instruction run so far
+    q           quit";
+
+fn run_debug(input: Option<String>) -> i32 {
+    let program = match load_program(input) {
+        Some(program) => program,
+        None => return 1,
+    };
+    let mut debugger = Debugger::new(Machine::new(program).with_trace());
+    let show = |debugger: &Debugger| {
+        let machine = debugger.machine();
+        match machine.current() {
+            Some(instr) => println!("pc {}: {}  acc={}", machine.pc(), instr, machine.acc()),
+            None => println!(
+                "pc {}: (outside the program)  acc={}",
+                machine.pc(),
+                machine.acc()
+            ),
+        }
+    };
+    let show_stop = |debugger: &Debugger, stop: Stop| {
+        match stop {
+            Stop::Breakpoint(b) => println!("breakpoint: {}", b),
+            Stop::Halted(halt) => println!("halted: {:?}", halt),
+        }
+        show(debugger);
+    };
+    show(&debugger);
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("(console) ");
+        io::stdout().flush().unwrap();
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            _ => return 0,
+        };
+        let words: Vec<_> = line.split_whitespace().collect();
+        match words.as_slice() {
+            [] => {}
+            ["q"] => return 0,
+            ["h"] => println!("{}", DEBUG_HELP),
+            ["s"] | ["s", _] => {
+                let n = match words.get(1).map(|n| n.parse::<u64>()) {
+                    None => 1,
+                    Some(Ok(n)) => n,
+                    Some(Err(_)) => {
+                        println!("expected a number of steps, found {:?}", words[1]);
+                        continue;
+                    }
+                };
+                match (0..n).try_for_each(|_| debugger.step()) {
+                    Ok(()) => show(&debugger),
+                    Err(stop) => show_stop(&debugger, stop),
+                }
+            }
+            ["c"] => {
+                let stop = debugger.resume();
+                show_stop(&debugger, stop);
+            }
+            [cmd @ "b", bp] | [cmd @ "d", bp] => match Breakpoint::parse(bp) {
+                Some(bp) if *cmd == "b" => debugger.add_breakpoint(bp),
+                Some(bp) => {
+                    if !debugger.remove_breakpoint(bp) {
+                        println!("there's no breakpoint at {}", bp);
+                    }
+                }
+                None => println!("expected a pc or acc=<value>, found {:?}", bp),
+            },
+            ["i"] => {
+                show(&debugger);
+                let machine = debugger.machine();
+                println!("steps: {}", machine.steps());
+                for bp in debugger.breakpoints() {
+                    println!("breakpoint: {}", bp);
+                }
+            }
+            ["t"] => print!("{}", debugger.machine().trace().unwrap().to_text()),
+            _ => println!("unknown command {:?}; type h for help", line.trim()),
+        }
+    }
+}
+
+/// Prints rows of cells in left-aligned columns.
+fn print_table(rows: &[Vec<String>]) {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|i| {
+            rows.iter()
+                .filter_map(|row| row.get(i))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    for row in rows {
+        let cells: Vec<_> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{:width$}", cell, width = width))
+            .collect();
+        println!("{}", cells.join("  ").trim_end());
+    }
 }
@@ -0,0 +1,502 @@
+//! Passport validation from day 4, driven by a schema rather than a `match` on every key.
+//!
+//! A schema is plain text, one field per line:
+//!
+//! ```text
+//! # comments and blank lines are ignored
+//! byr range 1920-2002
+//! hgt units 150-193cm 59-76in
+//! hcl regex #[0-9a-f]{6}
+//! ecl one-of amb blu brn gry grn hzl oth
+//! cid optional any
+//! ```
+//!
+//! Every field is required unless it's marked `optional`, and fields the schema doesn't list
+//! aren't allowed. Patterns have to match the whole value, so they don't need `^` and `$`, and
+//! they're compiled once, when the schema is parsed.
+//!
+//! `parse_records` reads passports in the puzzle's format, blank-line-separated records of
+//! key:value pairs, but doesn't give up on a record over one bad token.
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+
+use regex::Regex;
+
+use crate::error::{ParseError, Source};
+
+/// The rules from the puzzle description.
+pub const PUZZLE_SCHEMA: &str = "\
+byr range 1920-2002
+iyr range 2010-2020
+eyr range 2020-2030
+hgt units 150-193cm 59-76in
+hcl regex #[0-9a-f]{6}
+ecl one-of amb blu brn gry grn hzl oth
+pid regex [0-9]{9}
+cid optional any";
+
+/// What a field's value has to look like.
+#[derive(Debug, Clone)]
+pub enum Rule {
+    /// Anything goes.
+    Any,
+    /// A number in an inclusive range.
+    Range(i64, i64),
+    /// A number followed by one of the units, in that unit's inclusive range, e.g. "183cm".
+    Units(Vec<(String, i64, i64)>),
+    /// Matches the whole value.
+    Pattern(Regex),
+    OneOf(Vec<String>),
+}
+
+impl Rule {
+    /// Why `value` breaks the rule, if it does.
+    pub fn check(&self, value: &str) -> Option<String> {
+        match self {
+            Rule::Any => None,
+            Rule::Range(min, max) => match value.parse::<i64>() {
+                Ok(n) if (*min..=*max).contains(&n) => None,
+                Ok(n) => Some(format!("{} isn't between {} and {}", n, min, max)),
+                Err(_) => Some(format!("{:?} isn't a number", value)),
+            },
+            Rule::Units(units) => {
+                let split = value
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(value.len());
+                let (number, unit) = value.split_at(split);
+                let names: Vec<_> = units.iter().map(|(name, _, _)| name.as_str()).collect();
+                let (_, min, max) = match units.iter().find(|(name, _, _)| name == unit) {
+                    Some(found) => found,
+                    None if unit.is_empty() => {
+                        return Some(format!("{:?} needs a unit ({})", value, names.join(" or ")))
+                    }
+                    None => {
+                        return Some(format!(
+                            "{:?} isn't a unit ({} are)",
+                            unit,
+                            names.join(" and ")
+                        ))
+                    }
+                };
+                match number.parse::<i64>() {
+                    Ok(n) if (*min..=*max).contains(&n) => None,
+                    Ok(n) => Some(format!(
+                        "{}{} isn't between {}{} and {}{}",
+                        n, unit, min, unit, max, unit
+                    )),
+                    Err(_) => Some(format!("{:?} doesn't start with a number", value)),
+                }
+            }
+            Rule::Pattern(re) if re.is_match(value) => None,
+            Rule::Pattern(re) => Some(format!(
+                "{:?} doesn't match {}",
+                value,
+                // Without the anchors we added
+                &re.as_str()[4..re.as_str().len() - 2]
+            )),
+            Rule::OneOf(options) if options.iter().any(|o| o == value) => None,
+            Rule::OneOf(options) => {
+                Some(format!("{:?} isn't one of {}", value, options.join(", ")))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Field {
+    pub key: String,
+    pub required: bool,
+    pub rule: Rule,
+}
+
+#[derive(Debug, Clone)]
+pub struct Schema {
+    fields: Vec<Field>,
+}
+
+impl Schema {
+    /// Parses a schema in the format described at the top of this module. Errors point at
+    /// the line in the schema, though they say they're about day 4's input.
+    pub fn parse(text: &str) -> Result<Schema, ParseError> {
+        let src = Source::new(4, text);
+        let mut fields: Vec<Field> = vec![];
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut words = line.split_whitespace();
+            let key = words.next().unwrap();
+            if fields.iter().any(|f| f.key == key) {
+                return Err(src.error(line, format!("field {:?} is listed twice", key)));
+            }
+            let mut kind = words
+                .next()
+                .ok_or_else(|| src.error(line, format!("field {:?} has no rule", key)))?;
+            let required = kind != "optional";
+            if !required {
+                kind = words
+                    .next()
+                    .ok_or_else(|| src.error(line, format!("field {:?} has no rule", key)))?;
+            }
+            let args: Vec<&str> = words.collect();
+            let rule = match (kind, &args[..]) {
+                ("any", []) => Rule::Any,
+                ("range", [range]) => {
+                    let (min, max) = parse_range(&src, range)?;
+                    Rule::Range(min, max)
+                }
+                ("units", units) if !units.is_empty() => {
+                    let mut parsed = vec![];
+                    for unit in units {
+                        let split = unit
+                            .rfind(|c: char| c.is_ascii_digit())
+                            .map_or(0, |i| i + 1);
+                        let (min, max) = parse_range(&src, &unit[..split])?;
+                        parsed.push((unit[split..].to_owned(), min, max));
+                    }
+                    Rule::Units(parsed)
+                }
+                ("regex", [pattern]) => {
+                    let anchored = format!("^(?:{})$", pattern);
+                    let re = Regex::new(&anchored)
+                        .map_err(|e| src.error(pattern, format!("bad regex: {}", e)))?;
+                    Rule::Pattern(re)
+                }
+                ("one-of", options) if !options.is_empty() => {
+                    Rule::OneOf(options.iter().map(|o| o.to_string()).collect())
+                }
+                _ => {
+                    return Err(src.error(
+                        kind,
+                        format!(
+                            "expected any, range <min>-<max>, units <min>-<max><unit>..., \
+                             regex <pattern> or one-of <value>..., found {:?}",
+                            line
+                        ),
+                    ))
+                }
+            };
+            fields.push(Field {
+                key: key.to_owned(),
+                required,
+                rule,
+            });
+        }
+        Ok(Schema { fields })
+    }
+
+    pub fn puzzle() -> Schema {
+        Schema::parse(PUZZLE_SCHEMA).unwrap()
+    }
+
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    /// Checks a passport's fields, by key, against the schema.
+    pub fn validate(&self, fields: &HashMap<String, String>) -> Report {
+        let mut errors = vec![];
+        for field in &self.fields {
+            match fields.get(&field.key) {
+                None if field.required => errors.push(FieldError {
+                    key: field.key.clone(),
+                    problem: Problem::Missing,
+                }),
+                None => {}
+                Some(value) => {
+                    if let Some(reason) = field.rule.check(value) {
+                        errors.push(FieldError {
+                            key: field.key.clone(),
+                            problem: Problem::Invalid(reason),
+                        });
+                    }
+                }
+            }
+        }
+        let mut unknown: Vec<_> = fields
+            .keys()
+            .filter(|key| !self.fields.iter().any(|f| &f.key == *key))
+            .collect();
+        unknown.sort_unstable();
+        for key in unknown {
+            errors.push(FieldError {
+                key: key.clone(),
+                problem: Problem::Unknown,
+            });
+        }
+        Report { errors }
+    }
+}
+
+/// A "<min>-<max>" range, both ends inclusive.
+fn parse_range(src: &Source, range: &str) -> Result<(i64, i64), ParseError> {
+    let dash = range
+        .find('-')
+        .ok_or_else(|| src.error(range, format!("expected <min>-<max>, found {:?}", range)))?;
+    Ok((src.number(&range[..dash])?, src.number(&range[dash + 1..])?))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// A required field isn't there.
+    Missing,
+    /// The schema doesn't have this field. It's only a warning: it doesn't make the
+    /// passport invalid.
+    Unknown,
+    /// The value breaks the field's rule, for this reason.
+    Invalid(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    pub key: String,
+    pub problem: Problem,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.problem {
+            Problem::Missing => write!(f, "{} is missing", self.key),
+            Problem::Unknown => write!(f, "{} isn't a passport field", self.key),
+            Problem::Invalid(reason) => write!(f, "{}: {}", self.key, reason),
+        }
+    }
+}
+
+/// Everything wrong with one passport: missing fields and invalid values in the schema's
+/// order, and then any fields the schema doesn't have, which are only warnings.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Report {
+    pub errors: Vec<FieldError>,
+}
+
+impl Report {
+    /// Whether the right fields are there, whatever their values (part 1).
+    pub fn has_required_fields(&self) -> bool {
+        self.errors.iter().all(|e| e.problem != Problem::Missing)
+    }
+
+    /// Whether the right fields are there, with valid values (part 2).
+    pub fn is_valid(&self) -> bool {
+        self.errors.iter().all(|e| e.problem == Problem::Unknown)
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.errors.is_empty() {
+            return write!(f, "valid");
+        }
+        let errors: Vec<_> = self.errors.iter().map(|e| e.to_string()).collect();
+        write!(f, "{}", errors.join("; "))
+    }
+}
+
+/// Something odd about a record that didn't stop it being read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    /// The byte range of the token it's about.
+    pub span: Range<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "bytes {:?}: {}", self.span, self.message)
+    }
+}
+
+/// One passport's worth of key:value pairs, as it appears in the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record<'a> {
+    /// The byte range of the record, from the start of its first token to the end of its last.
+    pub span: Range<usize>,
+    /// In the order they appear. Unknown keys are kept, but for a duplicated key only the
+    /// first value is.
+    pub fields: Vec<(&'a str, &'a str)>,
+    pub warnings: Vec<Warning>,
+}
+
+impl<'a> Record<'a> {
+    pub fn get(&self, key: &str) -> Option<&'a str> {
+        self.fields.iter().find(|(k, _)| *k == key).map(|&(_, v)| v)
+    }
+
+    pub fn to_map(&self) -> HashMap<String, String> {
+        self.fields
+            .iter()
+            .map(|&(k, v)| (k.to_owned(), v.to_owned()))
+            .collect()
+    }
+}
+
+/// Splits `input` into records. Records are separated by blank lines, which can have
+/// whitespace on them; lines can end in "\r\n" and have trailing whitespace. Keys the schema
+/// doesn't have, keys that appear twice and tokens that aren't key:value pairs are warnings on
+/// their record, rather than errors.
+pub fn parse_records<'a>(input: &'a str, schema: &Schema) -> Vec<Record<'a>> {
+    let mut records = vec![];
+    let mut current: Option<Record> = None;
+    let mut line_start = 0;
+    for line in input.split_inclusive('\n') {
+        let offset = line_start;
+        line_start += line.len();
+        if line.trim().is_empty() {
+            records.extend(current.take());
+            continue;
+        }
+        for token in line.split_whitespace() {
+            let start = offset + (token.as_ptr() as usize - line.as_ptr() as usize);
+            let span = start..start + token.len();
+            let record = current.get_or_insert_with(|| Record {
+                span: span.clone(),
+                fields: vec![],
+                warnings: vec![],
+            });
+            record.span.end = span.end;
+            // (the field to keep, and what to warn about)
+            let (field, warning) = match token.find(':') {
+                Some(colon) if colon > 0 => {
+                    let (key, value) = (&token[..colon], &token[colon + 1..]);
+                    match record.get(key) {
+                        Some(first) => (
+                            None,
+                            Some(format!(
+                                "{} appears more than once; keeping the first value, {:?}",
+                                key, first
+                            )),
+                        ),
+                        None if !schema.fields.iter().any(|f| f.key == key) => (
+                            Some((key, value)),
+                            Some(format!("{} isn't a passport field", key)),
+                        ),
+                        None => (Some((key, value)), None),
+                    }
+                }
+                _ => (
+                    None,
+                    Some(format!(
+                        "expected key:value, found {:?}; skipping it",
+                        token
+                    )),
+                ),
+            };
+            record.fields.extend(field);
+            if let Some(message) = warning {
+                record.warnings.push(Warning { span, message });
+            }
+        }
+    }
+    records.extend(current);
+    records
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn passport(fields: &str) -> HashMap<String, String> {
+        fields
+            .split_whitespace()
+            .map(|pair| {
+                let (key, value) = pair.split_at(pair.find(':').unwrap());
+                (key.to_owned(), value[1..].to_owned())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_puzzle_schema() {
+        let schema = Schema::puzzle();
+        let valid =
+            passport("pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f");
+        assert!(schema.validate(&valid).is_valid());
+
+        let report = schema.validate(&passport(
+            "eyr:1972 cid:100 hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926",
+        ));
+        assert!(report.has_required_fields());
+        assert_eq!(
+            report.to_string(),
+            "eyr: 1972 isn't between 2020 and 2030; hgt: \"170\" needs a unit (cm or in); \
+             pid: \"186cm\" doesn't match [0-9]{9}"
+        );
+
+        // The old ecl regex, ^amb|blu|...|oth$, let this through
+        let report = schema.validate(&passport(
+            "ecl:ambx hgt:190in iyr:2015 byr:1990 hcl:#123abc pid:000000001 foo:bar",
+        ));
+        assert!(!report.has_required_fields());
+        assert_eq!(
+            report.to_string(),
+            "eyr is missing; hgt: 190in isn't between 59in and 76in; \
+             ecl: \"ambx\" isn't one of amb, blu, brn, gry, grn, hzl, oth; foo isn't a passport field"
+        );
+
+        // An extra key is reported, but the passport is still fine for both parts
+        let report = schema.validate(&passport(
+            "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f foo:bar",
+        ));
+        assert!(report.has_required_fields() && report.is_valid());
+        assert_eq!(report.to_string(), "foo isn't a passport field");
+    }
+
+    #[test]
+    fn test_custom_schema() {
+        let schema = Schema::parse("# just one\nwt optional units 1-10kg 2-22lb\n").unwrap();
+        assert!(schema.validate(&passport("")).is_valid());
+        assert!(schema.validate(&passport("wt:20lb")).is_valid());
+        assert!(!schema.validate(&passport("wt:20kg")).is_valid());
+        assert!(!schema.validate(&passport("wt:5st")).is_valid());
+
+        let err = Schema::parse("a any\nb range 1-\n").unwrap_err();
+        assert_eq!(err.line, 2);
+        assert!(Schema::parse("a regex (").is_err());
+        assert!(Schema::parse("a any\na any").is_err());
+        assert!(Schema::parse("a between 1 3").is_err());
+    }
+
+    #[test]
+    fn test_records() {
+        let input = "ecl:gry  pid:1 \r\nhgt:183cm\r\n \r\n\r\niyr:2013 iyr:2014 oops\nfoo:bar\n\n";
+        let records = parse_records(input, &Schema::puzzle());
+        assert_eq!(records.len(), 2);
+
+        assert_eq!(
+            &input[records[0].span.clone()],
+            "ecl:gry  pid:1 \r\nhgt:183cm"
+        );
+        assert_eq!(
+            records[0].fields,
+            vec![("ecl", "gry"), ("pid", "1"), ("hgt", "183cm")]
+        );
+        assert!(records[0].warnings.is_empty());
+
+        let second = &records[1];
+        assert_eq!(
+            &input[second.span.clone()],
+            "iyr:2013 iyr:2014 oops\nfoo:bar"
+        );
+        assert_eq!(second.get("iyr"), Some("2013"));
+        assert_eq!(second.get("foo"), Some("bar"));
+        let warnings: Vec<_> = second
+            .warnings
+            .iter()
+            .map(|w| (&input[w.span.clone()], w.message.as_str()))
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![
+                (
+                    "iyr:2014",
+                    "iyr appears more than once; keeping the first value, \"2013\""
+                ),
+                ("oops", "expected key:value, found \"oops\"; skipping it"),
+                ("foo:bar", "foo isn't a passport field"),
+            ]
+        );
+        assert!(parse_records("\n \n", &Schema::puzzle()).is_empty());
+    }
+}
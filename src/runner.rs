@@ -0,0 +1,251 @@
+//! Runs the solvers without going through `cargo aoc`.
+//!
+//! Days are looked up in the `solution::DAYS` registry. `cargo aoc` goes through the
+//! `#[aoc_generator]` and `#[aoc]` functions instead; the tests below check both ways give the
+//! same answers. Like `cargo aoc`, trailing newlines are trimmed off the input first.
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::day15;
+use crate::example::{self, Example};
+use crate::solution::{self, Day};
+
+/// Where `cargo aoc` keeps downloaded inputs, relative to the crate root.
+pub const DEFAULT_INPUT_DIR: &str = "input/2020";
+
+/// The answer from one solver, along with how long the generator and the solver took.
+#[derive(Debug)]
+pub struct Answer {
+    pub value: String,
+    pub generator: Duration,
+    pub runner: Duration,
+}
+
+/// Choices that change how some solvers go about it, but not their answers.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Config {
+    /// How day 15 remembers the numbers spoken.
+    pub day15_engine: day15::Engine,
+}
+
+impl Config {
+    /// The solution `run_with` uses for the given day.
+    pub fn day(&self, day: u32) -> Option<&'static Day> {
+        match (day, self.day15_engine) {
+            (15, day15::Engine::Hash) => Some(&solution::DAY15_HASH),
+            _ => solution::day(day),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct NoSolver {
+    pub day: u32,
+    pub part: u32,
+}
+
+impl fmt::Display for NoSolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no solver for day {} part {}", self.day, self.part)
+    }
+}
+
+impl Error for NoSolver {}
+
+/// The solver ran, but didn't find an answer in the input.
+#[derive(Debug)]
+pub struct NoAnswer {
+    pub day: u32,
+    pub part: u32,
+}
+
+impl fmt::Display for NoAnswer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "day {} part {} didn't find an answer in this input",
+            self.day, self.part
+        )
+    }
+}
+
+impl Error for NoAnswer {}
+
+/// The parts that have a solver for the given day. Day 25 only has a part 1 - the second star
+/// is free once you have the other 49.
+pub fn parts(day: u32) -> &'static [u32] {
+    solution::day(day).map(|d| d.parts).unwrap_or(&[])
+}
+
+/// Runs the parser and then the solver for one day and part.
+pub fn run(day: u32, part: u32, input: &str) -> Result<Answer, Box<dyn Error>> {
+    run_with(&Config::default(), day, part, input)
+}
+
+/// Like `run`, with the solvers set up as `config` says.
+pub fn run_with(
+    config: &Config,
+    day: u32,
+    part: u32,
+    input: &str,
+) -> Result<Answer, Box<dyn Error>> {
+    run_on(config.day(day), day, part, input)
+}
+
+fn run_on(
+    solution: Option<&Day>,
+    day: u32,
+    part: u32,
+    input: &str,
+) -> Result<Answer, Box<dyn Error>> {
+    let no_solver = || Box::new(NoSolver { day, part });
+    let solution = solution.ok_or_else(no_solver)?;
+    if !solution.parts.contains(&part) {
+        return Err(no_solver());
+    }
+    let start = Instant::now();
+    let parsed = solution.parse(input.trim_end_matches('\n'))?;
+    let generated = Instant::now();
+    // We've already checked the part exists, so this is the solver coming up empty
+    let value = solution
+        .solve(&parsed, part)
+        .ok_or_else(|| Box::new(NoAnswer { day, part }))?;
+    let done = Instant::now();
+    Ok(Answer {
+        value,
+        generator: generated - start,
+        runner: done - generated,
+    })
+}
+
+/// Runs one registered example, returning the answer the solver gave for it.
+/// The caller compares it against `example.expected`.
+pub fn run_example(day: u32, example: &Example) -> Result<Answer, Box<dyn Error>> {
+    run_on(example::solution(day), day, example.part, example.input)
+}
+
+/// The path `cargo aoc` would use for the given day's input inside `dir`.
+pub fn input_path(dir: &Path, day: u32) -> PathBuf {
+    dir.join(format!("day{}.txt", day))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use aoc_runner::{ArcStr, Runner};
+
+    use crate::*;
+
+    macro_rules! dispatch {
+        ($day:expr, $part:expr, $input:expr; $(($d:literal, $p:literal) => $method:ident),* $(,)?) => {
+            match ($day, $part) {
+                $(($d, $p) => Factory::$method($input),)*
+                (day, part) => Err(Box::new(NoSolver { day, part }) as Box<dyn Error>),
+            }
+        };
+    }
+
+    fn generate(day: u32, part: u32, input: ArcStr) -> Result<Box<dyn Runner>, Box<dyn Error>> {
+        dispatch!(day, part, input;
+            (1, 1) => day1_part1, (1, 2) => day1_part2,
+            (2, 1) => day2_part1, (2, 2) => day2_part2,
+            (3, 1) => day3_part1, (3, 2) => day3_part2,
+            (4, 1) => day4_part1, (4, 2) => day4_part2,
+            (5, 1) => day5_part1, (5, 2) => day5_part2,
+            (6, 1) => day6_part1, (6, 2) => day6_part2,
+            (7, 1) => day7_part1, (7, 2) => day7_part2,
+            (8, 1) => day8_part1, (8, 2) => day8_part2,
+            (9, 1) => day9_part1, (9, 2) => day9_part2,
+            (10, 1) => day10_part1, (10, 2) => day10_part2,
+            (11, 1) => day11_part1, (11, 2) => day11_part2,
+            (12, 1) => day12_part1, (12, 2) => day12_part2,
+            (13, 1) => day13_part1, (13, 2) => day13_part2,
+            (14, 1) => day14_part1, (14, 2) => day14_part2,
+            (15, 1) => day15_part1, (15, 2) => day15_part2,
+            (16, 1) => day16_part1, (16, 2) => day16_part2,
+            (17, 1) => day17_part1, (17, 2) => day17_part2,
+            (18, 1) => day18_part1, (18, 2) => day18_part2,
+            (19, 1) => day19_part1, (19, 2) => day19_part2,
+            (20, 1) => day20_part1, (20, 2) => day20_part2,
+            (21, 1) => day21_part1, (21, 2) => day21_part2,
+            (22, 1) => day22_part1, (22, 2) => day22_part2,
+            (23, 1) => day23_part1, (23, 2) => day23_part2,
+            (24, 1) => day24_part1, (24, 2) => day24_part2,
+            (25, 1) => day25_part1,
+        )
+    }
+
+    /// Runs a day the way `cargo aoc` does.
+    fn run_aoc(day: u32, part: u32, input: &str) -> Result<String, Box<dyn Error>> {
+        Ok(generate(day, part, ArcStr::from(input))?
+            .try_run()?
+            .to_string())
+    }
+
+    const EXAMPLE: &str = "1721\n979\n366\n299\n675\n1456\n";
+
+    #[test]
+    fn test_run() {
+        assert_eq!(run(1, 1, EXAMPLE).unwrap().value, "514579");
+        assert_eq!(run(1, 2, EXAMPLE).unwrap().value, "241861950");
+    }
+
+    #[test]
+    fn test_examples() {
+        for day in 1..=25 {
+            for example in crate::example::examples(day) {
+                let answer = run_example(day, example).unwrap();
+                assert_eq!(answer.value, example.expected, "day {}", day);
+                // cargo aoc can only run a day the way it runs the real inputs
+                if !std::ptr::eq(example::solution(day).unwrap(), solution::day(day).unwrap()) {
+                    continue;
+                }
+                let aoc = run_aoc(day, example.part, example.input).unwrap();
+                assert_eq!(aoc, example.expected, "day {} under cargo aoc", day);
+            }
+        }
+    }
+
+    #[test]
+    fn test_config() {
+        let hash = Config {
+            day15_engine: day15::Engine::Hash,
+        };
+        assert_eq!(run_with(&hash, 15, 1, "0,3,6\n").unwrap().value, "436");
+        assert_eq!(run_with(&hash, 1, 1, EXAMPLE).unwrap().value, "514579");
+        assert!(std::ptr::eq(hash.day(15).unwrap(), &solution::DAY15_HASH));
+        assert!(std::ptr::eq(
+            Config::default().day(15).unwrap(),
+            solution::day(15).unwrap()
+        ));
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let err = run(8, 1, "nop +0\njpm +4\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 8 input, line 2, column 1: unknown opcode \"jpm\""
+        );
+    }
+
+    #[test]
+    fn test_no_answer() {
+        let err = run(1, 2, "1\n2\n3\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 1 part 2 didn't find an answer in this input"
+        );
+        assert!(run_aoc(1, 2, "1\n2\n3\n").is_err());
+    }
+
+    #[test]
+    fn test_no_solver() {
+        assert!(run(25, 2, "").is_err());
+        assert!(run(26, 1, "").is_err());
+        assert_eq!(parts(25), &[1]);
+    }
+}
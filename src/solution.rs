@@ -0,0 +1,195 @@
+//! A common interface to every day, that doesn't depend on the aoc-runner macros.
+//!
+//! Each day module has a unit struct (`day8::Day8` and so on) implementing `Solution`, which
+//! ties together the day's parser and solvers. `Solution` has associated types, so it can't be
+//! used as a trait object; `DAYS` is the registry that erases them, so tools can loop over
+//! every day, and time parsing and solving separately.
+use std::any::Any;
+use std::fmt;
+
+use crate::error::ParseError;
+use crate::*;
+
+pub trait Solution {
+    /// What `parse` turns the puzzle input into. Both parts are solved from the same parsed input.
+    type Input: 'static;
+    type Part1: Output;
+    type Part2: Output;
+
+    /// The parts this day has. Day 25 only has a part 1.
+    const PARTS: &'static [u32] = &[1, 2];
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError>;
+    fn part1(input: &Self::Input) -> Self::Part1;
+    fn part2(input: &Self::Input) -> Self::Part2;
+}
+
+/// What a solver gives back. Usually that's just the answer, but a solver that can come up
+/// empty on an input that breaks the puzzle's promises says so with an `Option`, rather than
+/// panicking.
+pub trait Output {
+    /// The answer, ready to print, or `None` if there isn't one.
+    fn answer(self) -> Option<String>;
+}
+
+// Anything `Display` would do, but then `Option` couldn't have its own impl.
+macro_rules! display_output {
+    ($($t:ty),*) => {
+        $(impl Output for $t {
+            fn answer(self) -> Option<String> {
+                Some(self.to_string())
+            }
+        })*
+    };
+}
+
+display_output!(i32, i64, u32, u64, usize, String, num_bigint::BigUint);
+
+impl<T: Output> Output for Option<T> {
+    fn answer(self) -> Option<String> {
+        self.and_then(Output::answer)
+    }
+}
+
+/// A parsed input, from `Day::parse`. It can only be solved by the day that parsed it.
+pub struct Parsed {
+    day: u32,
+    input: Box<dyn Any>,
+}
+
+impl fmt::Debug for Parsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Parsed {{ day: {} }}", self.day)
+    }
+}
+
+/// One day's `Solution`, with the types erased.
+pub struct Day {
+    pub day: u32,
+    pub parts: &'static [u32],
+    parse: fn(&str) -> Result<Box<dyn Any>, ParseError>,
+    solve: fn(&dyn Any, u32) -> Option<String>,
+}
+
+impl Day {
+    pub const fn new<S: Solution>(day: u32) -> Self {
+        Day {
+            day,
+            parts: S::PARTS,
+            parse: parse_any::<S>,
+            solve: solve_any::<S>,
+        }
+    }
+
+    pub fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Parsed {
+            day: self.day,
+            input: (self.parse)(input)?,
+        })
+    }
+
+    /// Solves one part, or gives `None` if this day doesn't have that part or the solver
+    /// didn't find an answer.
+    ///
+    /// Panics if `input` was parsed by a different day.
+    pub fn solve(&self, input: &Parsed, part: u32) -> Option<String> {
+        assert_eq!(
+            input.day, self.day,
+            "day {} can't solve day {}'s input",
+            self.day, input.day
+        );
+        if self.parts.contains(&part) {
+            (self.solve)(input.input.as_ref(), part)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Debug for Day {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Day")
+            .field("day", &self.day)
+            .field("parts", &self.parts)
+            .finish()
+    }
+}
+
+fn parse_any<S: Solution>(input: &str) -> Result<Box<dyn Any>, ParseError> {
+    Ok(Box::new(S::parse(input)?))
+}
+
+fn solve_any<S: Solution>(input: &dyn Any, part: u32) -> Option<String> {
+    // `Day::solve` has already checked the input came from this day
+    let input = input.downcast_ref::<S::Input>().unwrap();
+    match part {
+        1 => S::part1(input).answer(),
+        _ => S::part2(input).answer(),
+    }
+}
+
+pub static DAYS: [Day; 25] = [
+    Day::new::<day1::Day1>(1),
+    Day::new::<day2::Day2>(2),
+    Day::new::<day3::Day3>(3),
+    Day::new::<day4::Day4>(4),
+    Day::new::<day5::Day5>(5),
+    Day::new::<day6::Day6>(6),
+    Day::new::<day7::Day7>(7),
+    Day::new::<day8::Day8>(8),
+    Day::new::<day9::Day9>(9),
+    Day::new::<day10::Day10>(10),
+    Day::new::<day11::Day11>(11),
+    Day::new::<day12::Day12>(12),
+    Day::new::<day13::Day13>(13),
+    Day::new::<day14::Day14>(14),
+    Day::new::<day15::Day15>(15),
+    Day::new::<day16::Day16>(16),
+    Day::new::<day17::Day17>(17),
+    Day::new::<day18::Day18>(18),
+    Day::new::<day19::Day19>(19),
+    Day::new::<day20::Day20>(20),
+    Day::new::<day21::Day21>(21),
+    Day::new::<day22::Day22>(22),
+    Day::new::<day23::Day23>(23),
+    Day::new::<day24::Day24>(24),
+    Day::new::<day25::Day25>(25),
+];
+
+/// Day 15 on the hash map engine instead of the array, for `runner::Config` to swap in.
+pub static DAY15_HASH: Day = Day::new::<day15::Day15Hash>(15);
+
+/// Day 9 with the example's shorter preamble, for `example::solution` to run its examples with.
+pub static DAY9_EXAMPLE: Day = Day::new::<day9::Day9Example>(9);
+
+/// Looks up a day in `DAYS`.
+pub fn day(day: u32) -> Option<&'static Day> {
+    DAYS.iter().find(|d| d.day == day)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_registry() {
+        for (i, d) in DAYS.iter().enumerate() {
+            assert_eq!(d.day as usize, i + 1);
+        }
+        let day1 = day(1).unwrap();
+        let parsed = day1.parse("1721\n979\n366\n299\n675\n1456").unwrap();
+        assert_eq!(day1.solve(&parsed, 1).unwrap(), "514579");
+        assert_eq!(day1.solve(&parsed, 3), None);
+        let parsed = day1.parse("1\n2").unwrap();
+        assert_eq!(day1.solve(&parsed, 1), None);
+        assert_eq!(day(25).unwrap().parts, &[1]);
+        assert!(day(26).is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_wrong_day() {
+        let parsed = day(1).unwrap().parse("1721\n979").unwrap();
+        day(2).unwrap().solve(&parsed, 1);
+    }
+}